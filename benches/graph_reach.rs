@@ -0,0 +1,151 @@
+// GRAPH REPRESENTATION BENCHMARK (String keys vs handles vs interned IDs)
+// Builds the same synthetic plant (a chain of units plus two random
+// cross-links each) as plain String adjacency lists, as a Graph with
+// handles and with interned IDs, then times a BFS over each
+// Run with `cargo bench --bench graph_reach`
+
+// The crate is a binary, so the module is compiled straight from src/.
+// Benches build with cfg(test), which pulls in the unit-test modules too
+#[path = "../src"]
+#[allow(dead_code, unused_imports)]
+mod src {
+    pub mod graph_demo;
+}
+
+use std::collections::{HashMap, HashSet};
+use std::hint::black_box;
+use std::mem::size_of;
+use std::time::Instant;
+
+use src::graph_demo::{Graph, GraphKind, InternedGraph, NodeIndex};
+
+const UNITS: usize = 20_000;
+
+fn main() {
+    // xorshift32: cheap, repeatable pseudo-random links
+    let mut state = 0x2545_F491u32;
+    let mut random_unit = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize % UNITS
+    };
+    let labels: Vec<String> = (0..UNITS).map(|i| format!("Unit-{:06}", i)).collect();
+    let mut pairs = HashSet::new();
+    for i in 0..UNITS {
+        for j in [i + 1, random_unit(), random_unit()] {
+            if j < UNITS && j != i {
+                pairs.insert((i.min(j), i.max(j)));
+            }
+        }
+    }
+    let mut pairs: Vec<(usize, usize)> = pairs.into_iter().collect();
+    pairs.sort();
+    let pairs = black_box(pairs);
+
+    // Every adjacency entry owns a clone of its neighbor's label
+    let start = Instant::now();
+    let mut keyed: HashMap<String, Vec<String>> = HashMap::new();
+    for &(a, b) in &pairs {
+        keyed.entry(labels[a].clone()).or_default().push(labels[b].clone());
+        keyed.entry(labels[b].clone()).or_default().push(labels[a].clone());
+    }
+    let keyed_build = start.elapsed();
+
+    // Labels are looked up once; edges then only name handles
+    let start = Instant::now();
+    let mut handled: Graph<String, ()> = Graph::new();
+    let handles: Vec<NodeIndex> = labels
+        .iter()
+        .map(|label| handled.add_node(label.clone()))
+        .collect();
+    for &(a, b) in &pairs {
+        handled.add_edge(handles[a], handles[b], ()).expect("units were just added");
+    }
+    let handled_build = start.elapsed();
+
+    let start = Instant::now();
+    let mut interned = InternedGraph::new(GraphKind::Undirected);
+    for &(a, b) in &pairs {
+        interned.add_edge(&labels[a], &labels[b]);
+    }
+    let interned_build = start.elapsed();
+
+    let start = Instant::now();
+    let csr = interned.to_csr();
+    let csr_build = start.elapsed();
+
+    let start = Instant::now();
+    let mut seen: HashSet<&str> = HashSet::from([labels[0].as_str()]);
+    let mut queue = vec![labels[0].as_str()];
+    let mut next = 0;
+    while let Some(&node) = queue.get(next) {
+        next += 1;
+        for neighbor in &keyed[node] {
+            if seen.insert(neighbor) {
+                queue.push(neighbor);
+            }
+        }
+    }
+    let keyed_reached = black_box(queue.len());
+    let keyed_bfs = start.elapsed();
+
+    let start = Instant::now();
+    let handled_reached = handled.bfs_iter(handles[0]).expect("unit 0 exists").count();
+    let handled_bfs = start.elapsed();
+
+    let first = interned.id(&labels[0]).expect("unit 0 exists");
+    let start = Instant::now();
+    let interned_reached = interned.bfs(first).len();
+    let interned_bfs = start.elapsed();
+
+    let start = Instant::now();
+    let csr_reached = csr.bfs(first).len();
+    let csr_bfs = start.elapsed();
+
+    let keyed_bytes = keyed.capacity() * size_of::<(String, Vec<String>)>()
+        + keyed
+            .iter()
+            .map(|(node, neighbors)| {
+                let owned: usize = neighbors.iter().map(String::capacity).sum();
+                node.capacity() + neighbors.capacity() * size_of::<String>() + owned
+            })
+            .sum::<usize>();
+
+    println!(
+        "Graph benchmark ({} units, {} links):",
+        interned.node_count(),
+        interned.edge_count()
+    );
+    println!(
+        "  {:<24} {:>10} {:>10} {:>9} {:>8}",
+        "Representation", "Build", "BFS", "Heap", "Reached"
+    );
+    let kib = |bytes: usize| format!("{} KiB", bytes / 1024);
+    for (label, build, bfs, bytes, reached) in [
+        ("HashMap<String, Vec>", keyed_build, keyed_bfs, kib(keyed_bytes), keyed_reached),
+        (
+            "Graph<String, ()>",
+            handled_build,
+            handled_bfs,
+            kib(handled.heap_bytes()),
+            handled_reached,
+        ),
+        (
+            "Interned Vec<Vec<Id>>",
+            interned_build,
+            interned_bfs,
+            kib(interned.heap_bytes()),
+            interned_reached,
+        ),
+        (
+            "CSR (+ interned labels)",
+            csr_build,
+            csr_bfs,
+            format!("+{}", kib(csr.heap_bytes())),
+            csr_reached,
+        ),
+    ] {
+        println!("  {:<24} {:>10.2?} {:>10.2?} {:>9} {:>8}", label, build, bfs, bytes, reached);
+    }
+}
@@ -0,0 +1,55 @@
+// INSERT BENCHMARK (Plain BST vs Red-Black Tree)
+// Times building both trees from the same sorted and pseudo-random streams
+// Sorted input degrades the plain BST into a linked list; the red-black
+// tree stays logarithmic
+// Run with `cargo bench --bench tree_inserts`
+
+// The crate is a binary, so the module is compiled straight from src/.
+// Benches build with cfg(test), which pulls in the unit-test modules too
+#[path = "../src"]
+#[allow(dead_code, unused_imports)]
+mod src {
+    pub mod tree_demo;
+}
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use src::tree_demo::{BinarySearchTree, RedBlackTree};
+
+const IDS: u32 = 3000;
+
+fn main() {
+    let sorted: Vec<u32> = (0..IDS).collect();
+    // xorshift32: cheap, repeatable pseudo-random IDs
+    let mut state = 0x2545_F491u32;
+    let random: Vec<u32> = (0..IDS)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state % (IDS * 10)
+        })
+        .collect();
+
+    println!("Insert benchmark ({} equipment IDs):", IDS);
+    println!("  {:<8} {:>12} {:>12}", "Stream", "Plain BST", "Red-Black");
+    for (label, stream) in [("sorted", &sorted), ("random", &random)] {
+        let start = Instant::now();
+        let plain: BinarySearchTree<u32> = black_box(stream).iter().copied().collect();
+        let plain_time = start.elapsed();
+
+        let start = Instant::now();
+        let red_black: RedBlackTree<u32> = black_box(stream).iter().copied().collect();
+        let red_black_time = start.elapsed();
+
+        println!(
+            "  {:<8} {:>12.2?} {:>12.2?}   (both hold {}, red-black height {})",
+            label,
+            plain_time,
+            red_black_time,
+            plain.len(),
+            red_black.height()
+        );
+    }
+}
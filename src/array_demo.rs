@@ -2,6 +2,33 @@
 // Arrays store elements in contiguous memory locations
 // - Fixed-size arrays: [T; N] - size known at compile time
 // - Dynamic arrays: Vec<T> - size can grow/shrink at runtime
+// - Segment and Fenwick trees: O(log n) range aggregates over an array
+
+mod fenwick;
+mod segment_tree;
+
+use std::ops::{Bound, RangeBounds};
+
+use fenwick::FenwickTree;
+use segment_tree::{Max, Min, SegmentTree, Sum};
+
+// Resolves any range over 0..len to [start, end), panicking like slice
+// indexing does when it is out of bounds
+fn bounds(range: impl RangeBounds<usize>, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "range starts at {} but ends at {}", start, end);
+    assert!(end <= len, "range end {} out of bounds for length {}", end, len);
+    (start, end)
+}
 
 pub fn demonstrate() {
     println!("Arrays provide O(1) access by index\n");
@@ -20,13 +47,13 @@ pub fn demonstrate() {
 
     // Dynamic array (Vector)
     println!("\nDynamic array (Vec):");
-    let mut pressures: Vec<f64> = Vec::new();
-    
     // Simulating sensor readings
-    pressures.push(101.3); // kPa
-    pressures.push(102.1);
-    pressures.push(100.8);
-    pressures.push(101.5);
+    let mut pressures: Vec<f64> = vec![
+        101.3, // kPa
+        102.1,
+        100.8,
+        101.5,
+    ];
     
     println!("  Pressure readings (kPa): {:?}", pressures);
     println!("  Count: {}", pressures.len());
@@ -43,4 +70,74 @@ pub fn demonstrate() {
     for (index, &pressure) in pressures.iter().enumerate() {
         println!("    Reading {}: {:.2} kPa", index + 1, pressure);
     }
+
+    // Range aggregates in O(log n) instead of re-summing the array
+    println!("\nSegment trees over the temperatures (sum, min, max):");
+    let mut sums: SegmentTree<Sum> = SegmentTree::new(&temperatures);
+    let mut lows: SegmentTree<Min> = SegmentTree::new(&temperatures);
+    let mut highs: SegmentTree<Max> = SegmentTree::new(&temperatures);
+    println!("  Average: {:.2}°C (from one query)", sums.query(..) / sums.len() as f64);
+
+    let rolling = |sums: &SegmentTree<Sum>, lows: &SegmentTree<Min>, highs: &SegmentTree<Max>| {
+        // No windows at all when fewer than 3 readings are stored
+        for start in 0..sums.len().saturating_sub(2) {
+            let window = start..start + 3;
+            println!(
+                "    Readings {}-{}: avg {:.2}, min {:.1}, max {:.1}",
+                start + 1,
+                start + 3,
+                sums.query(window.clone()) / 3.0,
+                lows.query(window.clone()),
+                highs.query(window)
+            );
+        }
+    };
+    println!("  Rolling 3-reading windows:");
+    rolling(&sums, &lows, &highs);
+
+    // Sensors 2-4 read 0.5°C low: one lazy range update per tree
+    sums.update_range(1..4, 0.5);
+    lows.update_range(1..4, 0.5);
+    highs.update_range(1..4, 0.5);
+    // A fresh reading replaces the first one
+    sums.set(0, 22.9);
+    lows.set(0, 22.9);
+    highs.set(0, 22.9);
+    println!("  After +0.5°C calibration on 2-4 and a new first reading:");
+    rolling(&sums, &lows, &highs);
+
+    println!(
+        "  Lowest overall {:.1}°C, reading 4 now {:.1}°C",
+        lows.query(..),
+        highs.get(3)
+    );
+    println!(
+        "  Empty tree? {}, empty range sum: {}",
+        SegmentTree::<Sum>::new(&[]).is_empty(),
+        sums.query(2..2)
+    );
+
+    // Prefix sums over the remaining pressure readings
+    println!("\nFenwick tree over the pressures (prefix sums):");
+    let mut totals = FenwickTree::from_slice(&pressures);
+    for end in 1..=totals.len() {
+        println!("  Sum of first {}: {:.1} kPa", end, totals.prefix_sum(end));
+    }
+    totals.add(1, 0.4);
+    println!("  Reading 2 corrected by +0.4 kPa: {:.1}", totals.get(1));
+    println!("  Average of readings 2-3: {:.2} kPa", totals.range_sum(1..) / 2.0);
+    totals.set(0, 101.0);
+    println!("  Reading 1 reset to 101.0 kPa, total now {:.1} kPa", totals.range_sum(..));
+
+    // Works for any summable type: alarm counts per hour of the day
+    let mut alarms: FenwickTree<u32> = FenwickTree::new(24);
+    for hour in [2, 9, 9, 13, 17, 23] {
+        alarms.add(hour, 1);
+    }
+    println!(
+        "  Alarms before noon: {}, 12:00-18:00: {}, empty? {}",
+        alarms.prefix_sum(12),
+        alarms.range_sum(12..18),
+        alarms.is_empty()
+    );
 }
@@ -0,0 +1,159 @@
+// FENWICK TREE (Binary Indexed Tree)
+// A plain array where slot i (1-based) holds the sum of the i & -i
+// elements ending at i; the lowest set bit of an index says how far back
+// its slot reaches
+// A prefix sum adds up O(log n) slots, stripping the lowest bit each step;
+// a point update touches O(log n) slots, adding the lowest bit each step
+// Smaller and simpler than a segment tree, but only for invertible
+// operations like sums (range sum = prefix(end) - prefix(start))
+// Use cases: running totals, cumulative counts, order statistics
+
+use std::ops::{Add, RangeBounds, Sub};
+
+use super::bounds;
+
+pub struct FenwickTree<T> {
+    tree: Vec<T>, // tree[0] is unused so the bit tricks work on 1-based indices
+}
+
+fn lowest_bit(index: usize) -> usize {
+    index & index.wrapping_neg()
+}
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    // All zeros
+    pub fn new(len: usize) -> Self {
+        FenwickTree {
+            tree: vec![T::default(); len + 1],
+        }
+    }
+
+    // O(n) build: each slot passes its total on to the next slot covering it
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut tree = vec![T::default(); values.len() + 1];
+        tree[1..].copy_from_slice(values);
+        for index in 1..tree.len() {
+            let parent = index + lowest_bit(index);
+            if parent < tree.len() {
+                tree[parent] = tree[parent] + tree[index];
+            }
+        }
+        FenwickTree { tree }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Adds `delta` to element `index` (0-based)
+    pub fn add(&mut self, index: usize, delta: T) {
+        assert!(index < self.len(), "index {} out of bounds for length {}", index, self.len());
+        let mut slot = index + 1;
+        while slot < self.tree.len() {
+            self.tree[slot] = self.tree[slot] + delta;
+            slot += lowest_bit(slot);
+        }
+    }
+
+    // Sum of the first `end` elements, i.e. of 0..end
+    pub fn prefix_sum(&self, end: usize) -> T {
+        assert!(end <= self.len(), "prefix end {} out of bounds for length {}", end, self.len());
+        let mut total = T::default();
+        let mut slot = end;
+        while slot > 0 {
+            total = total + self.tree[slot];
+            slot -= lowest_bit(slot);
+        }
+        total
+    }
+
+    pub fn range_sum(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = bounds(range, self.len());
+        self.prefix_sum(end) - self.prefix_sum(start)
+    }
+
+    pub fn get(&self, index: usize) -> T {
+        self.range_sum(index..=index)
+    }
+
+    // Point assignment, expressed as the difference from the current value
+    pub fn set(&mut self, index: usize, value: T) {
+        let delta = value - self.get(index);
+        self.add(index, delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random operations without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // Offsets of a few tenths never add up to exact binary fractions
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    // A reading between 0.0 and 50.0 in tenths
+    fn reading(state: &mut u64) -> f64 {
+        (next(state) % 501) as f64 / 10.0
+    }
+
+    // Random point adds and sets, checked against the plain readings
+    #[test]
+    fn matches_a_naive_vec() {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        for len in 0..40 {
+            let mut model: Vec<f64> = (0..len).map(|_| reading(&mut state)).collect();
+            let mut totals = FenwickTree::from_slice(&model);
+            for _ in 0..200 {
+                if len > 0 {
+                    let index = next(&mut state) as usize % len;
+                    let value = reading(&mut state) - 25.0;
+                    if next(&mut state).is_multiple_of(2) {
+                        model[index] += value;
+                        totals.add(index, value);
+                    } else {
+                        model[index] = value;
+                        totals.set(index, value);
+                    }
+                    assert!(close(totals.get(index), model[index]));
+                }
+                let a = next(&mut state) as usize % (len + 1);
+                let b = next(&mut state) as usize % (len + 1);
+                let (from, to) = (a.min(b), a.max(b));
+                assert!(close(totals.range_sum(from..to), model[from..to].iter().sum()));
+                assert!(close(totals.prefix_sum(to), model[..to].iter().sum()));
+            }
+        }
+    }
+
+    #[test]
+    fn from_slice_matches_point_adds() {
+        let counts = [3, 0, 7, 1, 1, 4, 0, 2, 9];
+        let bulk = FenwickTree::from_slice(&counts);
+        let mut added: FenwickTree<i64> = FenwickTree::new(counts.len());
+        for (index, &count) in counts.iter().enumerate() {
+            added.add(index, count);
+        }
+        for end in 0..=counts.len() {
+            let expected: i64 = counts[..end].iter().sum();
+            assert_eq!((bulk.prefix_sum(end), added.prefix_sum(end)), (expected, expected));
+        }
+        assert_eq!(bulk.range_sum(2..=5), 13);
+        assert!(FenwickTree::<i64>::new(0).is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+// SEGMENT TREE (with Lazy Propagation)
+// A complete binary tree over an array: each node stores the aggregate
+// (sum, min, max, ...) of one contiguous segment, the root covers it all
+// Any range splits into O(log n) stored segments, so range queries and
+// point updates are O(log n)
+// Range updates are lazy: a node fully inside the range records the
+// update and only pushes it to its children when a later call looks inside
+// The operation is a type parameter; it only has to be associative
+// Use cases: rolling dashboards, range min/max, calibration offsets
+
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+use super::bounds;
+
+// An associative aggregate plus the range update it supports
+pub trait RangeOp {
+    type Value: Clone;
+    type Update: Clone;
+
+    // Aggregate of an empty segment: combine(identity, x) == x
+    fn identity() -> Self::Value;
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+    // Aggregate of `len` elements after `update` was applied to each
+    fn apply(aggregate: &Self::Value, update: &Self::Update, len: usize) -> Self::Value;
+    // One update with the effect of `first` followed by `second`
+    fn compose(first: &Self::Update, second: &Self::Update) -> Self::Update;
+}
+
+// Readings are f64; every op below supports "add an offset to a range"
+pub struct Sum;
+pub struct Min;
+pub struct Max;
+
+impl RangeOp for Sum {
+    type Value = f64;
+    type Update = f64;
+
+    fn identity() -> f64 {
+        0.0
+    }
+
+    fn combine(left: &f64, right: &f64) -> f64 {
+        left + right
+    }
+
+    fn apply(aggregate: &f64, offset: &f64, len: usize) -> f64 {
+        aggregate + offset * len as f64
+    }
+
+    fn compose(first: &f64, second: &f64) -> f64 {
+        first + second
+    }
+}
+
+impl RangeOp for Min {
+    type Value = f64;
+    type Update = f64;
+
+    fn identity() -> f64 {
+        f64::INFINITY
+    }
+
+    fn combine(left: &f64, right: &f64) -> f64 {
+        left.min(*right)
+    }
+
+    // Shifting every element shifts the minimum by the same amount
+    fn apply(aggregate: &f64, offset: &f64, _len: usize) -> f64 {
+        aggregate + offset
+    }
+
+    fn compose(first: &f64, second: &f64) -> f64 {
+        first + second
+    }
+}
+
+impl RangeOp for Max {
+    type Value = f64;
+    type Update = f64;
+
+    fn identity() -> f64 {
+        f64::NEG_INFINITY
+    }
+
+    fn combine(left: &f64, right: &f64) -> f64 {
+        left.max(*right)
+    }
+
+    fn apply(aggregate: &f64, offset: &f64, _len: usize) -> f64 {
+        aggregate + offset
+    }
+
+    fn compose(first: &f64, second: &f64) -> f64 {
+        first + second
+    }
+}
+
+// Nodes live in a Vec: node i has children 2i and 2i + 1, the root is 1
+pub struct SegmentTree<Op: RangeOp> {
+    len: usize,
+    tree: Vec<Op::Value>,
+    lazy: Vec<Option<Op::Update>>, // Pending update for a node's children
+    op: PhantomData<Op>,
+}
+
+impl<Op: RangeOp> SegmentTree<Op> {
+    pub fn new(values: &[Op::Value]) -> Self {
+        let size = 4 * values.len().max(1);
+        let mut segment_tree = SegmentTree {
+            len: values.len(),
+            tree: vec![Op::identity(); size],
+            lazy: vec![None; size],
+            op: PhantomData,
+        };
+        if !values.is_empty() {
+            segment_tree.build(1, 0, values.len(), values);
+        }
+        segment_tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn build(&mut self, node: usize, start: usize, end: usize, values: &[Op::Value]) {
+        if end - start == 1 {
+            self.tree[node] = values[start].clone();
+            return;
+        }
+        let mid = start + (end - start) / 2;
+        self.build(2 * node, start, mid, values);
+        self.build(2 * node + 1, mid, end, values);
+        self.pull(node);
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.tree[node] = Op::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    // Applies an update to a whole segment, deferring it for the children
+    fn apply(&mut self, node: usize, len: usize, update: &Op::Update) {
+        self.tree[node] = Op::apply(&self.tree[node], update, len);
+        if len > 1 {
+            self.lazy[node] = Some(match &self.lazy[node] {
+                Some(pending) => Op::compose(pending, update),
+                None => update.clone(),
+            });
+        }
+    }
+
+    // Hands a deferred update down before looking inside the segment
+    fn push(&mut self, node: usize, start: usize, mid: usize, end: usize) {
+        if let Some(update) = self.lazy[node].take() {
+            self.apply(2 * node, mid - start, &update);
+            self.apply(2 * node + 1, end - mid, &update);
+        }
+    }
+
+    // Reads without pushing: a pending update on this node is applied to
+    // the part of the answer that lies under it instead
+    fn query_node(
+        &self,
+        node: usize,
+        start: usize,
+        end: usize,
+        from: usize,
+        to: usize,
+    ) -> Op::Value {
+        if to <= start || end <= from {
+            return Op::identity();
+        }
+        if from <= start && end <= to {
+            return self.tree[node].clone();
+        }
+        let mid = start + (end - start) / 2;
+        let left = self.query_node(2 * node, start, mid, from, to);
+        let right = self.query_node(2 * node + 1, mid, end, from, to);
+        let combined = Op::combine(&left, &right);
+        match &self.lazy[node] {
+            Some(update) => {
+                let covered = end.min(to) - start.max(from);
+                Op::apply(&combined, update, covered)
+            }
+            None => combined,
+        }
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        start: usize,
+        end: usize,
+        from: usize,
+        to: usize,
+        update: &Op::Update,
+    ) {
+        if to <= start || end <= from {
+            return;
+        }
+        if from <= start && end <= to {
+            self.apply(node, end - start, update);
+            return;
+        }
+        let mid = start + (end - start) / 2;
+        self.push(node, start, mid, end);
+        self.update_node(2 * node, start, mid, from, to, update);
+        self.update_node(2 * node + 1, mid, end, from, to, update);
+        self.pull(node);
+    }
+
+    fn set_node(&mut self, node: usize, start: usize, end: usize, index: usize, value: Op::Value) {
+        if end - start == 1 {
+            self.tree[node] = value;
+            return;
+        }
+        let mid = start + (end - start) / 2;
+        self.push(node, start, mid, end);
+        if index < mid {
+            self.set_node(2 * node, start, mid, index, value);
+        } else {
+            self.set_node(2 * node + 1, mid, end, index, value);
+        }
+        self.pull(node);
+    }
+
+    // Aggregate over the range; the identity for an empty range
+    pub fn query(&self, range: impl RangeBounds<usize>) -> Op::Value {
+        let (from, to) = bounds(range, self.len);
+        if from == to {
+            return Op::identity();
+        }
+        self.query_node(1, 0, self.len, from, to)
+    }
+
+    pub fn get(&self, index: usize) -> Op::Value {
+        self.query(index..=index)
+    }
+
+    // Point update: replaces one element
+    pub fn set(&mut self, index: usize, value: Op::Value) {
+        assert!(
+            index < self.len,
+            "index {} out of bounds for length {}",
+            index,
+            self.len
+        );
+        self.set_node(1, 0, self.len, index, value);
+    }
+
+    // Range update: applies `update` to every element in the range
+    pub fn update_range(&mut self, range: impl RangeBounds<usize>, update: Op::Update) {
+        let (from, to) = bounds(range, self.len);
+        if from < to {
+            self.update_node(1, 0, self.len, from, to, &update);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random operations without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // Offsets of a few tenths never add up to exact binary fractions
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    // A reading between 0.0 and 50.0 in tenths
+    fn reading(state: &mut u64) -> f64 {
+        (next(state) % 501) as f64 / 10.0
+    }
+
+    // Random range offsets, point sets and queries on every tree kind,
+    // checked against the plain readings
+    #[test]
+    fn matches_a_naive_vec() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        for len in 1..40 {
+            let mut model: Vec<f64> = (0..len).map(|_| reading(&mut state)).collect();
+            let mut sums: SegmentTree<Sum> = SegmentTree::new(&model);
+            let mut lows: SegmentTree<Min> = SegmentTree::new(&model);
+            let mut highs: SegmentTree<Max> = SegmentTree::new(&model);
+            for _ in 0..200 {
+                let a = next(&mut state) as usize % (len + 1);
+                let b = next(&mut state) as usize % (len + 1);
+                let (from, to) = (a.min(b), a.max(b));
+                match next(&mut state) % 3 {
+                    0 => {
+                        let offset = reading(&mut state) - 25.0;
+                        model[from..to].iter_mut().for_each(|value| *value += offset);
+                        sums.update_range(from..to, offset);
+                        lows.update_range(from..to, offset);
+                        highs.update_range(from..to, offset);
+                    }
+                    1 if from < len => {
+                        let value = reading(&mut state);
+                        model[from] = value;
+                        sums.set(from, value);
+                        lows.set(from, value);
+                        highs.set(from, value);
+                    }
+                    _ => {}
+                }
+                let window = &model[from..to];
+                let low = window.iter().copied().fold(f64::INFINITY, f64::min);
+                let high = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                assert!(close(sums.query(from..to), window.iter().sum()));
+                assert_eq!(lows.query(from..to) == f64::INFINITY, from == to);
+                if from < to {
+                    assert!(close(lows.query(from..to), low));
+                    assert!(close(highs.query(from..=to - 1), high));
+                    assert!(close(highs.get(from), model[from]));
+                }
+            }
+            assert!(close(sums.query(..), model.iter().sum()));
+        }
+    }
+
+    #[test]
+    fn empty_trees_and_ranges_give_the_identity() {
+        let empty: SegmentTree<Sum> = SegmentTree::new(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.query(..), 0.0);
+        let highs: SegmentTree<Max> = SegmentTree::new(&[1.0, 2.0]);
+        assert_eq!((highs.len(), highs.query(1..1)), (2, f64::NEG_INFINITY));
+    }
+
+    #[test]
+    #[should_panic(expected = "range end 3 out of bounds for length 2")]
+    fn queries_past_the_end_panic() {
+        SegmentTree::<Min>::new(&[1.0, 2.0]).query(1..3);
+    }
+}
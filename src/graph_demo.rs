@@ -2,97 +2,718 @@
 // Collection of nodes (vertices) connected by edges
 // Can be directed or undirected, weighted or unweighted
 // Represented using adjacency list or adjacency matrix
+// Graph<N, E> carries a payload on every node (N) and edge (E), e.g.
+// Graph<String, ()> for plain labels or Graph<Equipment, Pipe> for real
+// plant data; nodes and edges are reached through Copy handles
+// (NodeIndex / EdgeIndex) that stay valid until that item is removed
 // Use cases: networks, social connections, routing, dependencies
 
-use std::collections::{HashMap, HashSet, VecDeque};
+mod connectivity;
+mod interned;
+mod topological;
+mod traversal;
 
-pub struct Graph {
-    // Adjacency list representation: node -> list of connected nodes
-    adjacency_list: HashMap<String, Vec<String>>,
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::mem::size_of;
+
+// Re-exported for benches/graph_reach.rs, which compiles this module on its own
+pub(crate) use interned::InternedGraph;
+use topological::OrderError;
+use traversal::{Step, Visitor};
+
+// Equipment tags shared with the other demos (e.g. trie_demo)
+pub const PLANT_EQUIPMENT: [&str; 7] = [
+    "Reactor",
+    "Heat Exchanger",
+    "Pump-A",
+    "Pump-B",
+    "Storage Tank",
+    "Control Valve",
+    "Separator",
+];
+pub const SENSOR_NETWORK: [&str; 5] = [
+    "Central Hub",
+    "Sensor-A",
+    "Sensor-B",
+    "Sensor-C",
+    "Sensor-D",
+];
+
+// Handles are slot numbers; slots of removed items are left empty rather
+// than reused, so a stale handle never points at a different node or edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeIndex(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeIndex(u32);
+
+impl NodeIndex {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl EdgeIndex {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// An edge as seen while walking from `source`; for undirected graphs
+// `target` is whichever end is not the node being walked from
+#[derive(Debug)]
+pub struct EdgeRef<'a, E> {
+    pub id: EdgeIndex,
+    pub source: NodeIndex,
+    pub target: NodeIndex,
+    pub weight: &'a E,
+}
+
+// Whether an edge can be followed in one direction or both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphKind {
+    // Process flow, dependencies: from → to only
+    Directed,
+    // Physical links: every edge is listed at both ends
+    Undirected,
+}
+
+// What add_edge does with an edge between nodes that are already joined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgePolicy {
+    // At most one edge per pair and no self-loops; repeats are rejected
+    Simple,
+    // Parallel edges and self-loops are kept, and each one counts
+    Multigraph,
+}
+
+#[derive(Debug, Clone)]
+struct NodeSlot<N> {
+    weight: N,
+    // Edges listed at this node; undirected graphs only use `outgoing`
+    outgoing: Vec<EdgeIndex>,
+    incoming: Vec<EdgeIndex>,
+}
+
+#[derive(Debug, Clone)]
+struct EdgeSlot<E> {
+    source: NodeIndex,
+    target: NodeIndex,
+    weight: E,
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph<N, E> {
+    kind: GraphKind,
+    policy: EdgePolicy,
+    // Adjacency list representation: each node slot lists its edges, and
+    // each edge (with its payload) is stored once, indexed by handle
+    nodes: Vec<Option<NodeSlot<N>>>,
+    edges: Vec<Option<EdgeSlot<E>>>,
+    node_count: usize,
+    edge_count: usize,
+}
+
+// Result of Dijkstra from one start node; only reachable nodes appear
+#[derive(Debug)]
+pub struct ShortestPaths {
+    pub distances: HashMap<NodeIndex, f64>,
+    // Node → the node before it on its cheapest path from the start
+    pub predecessors: HashMap<NodeIndex, NodeIndex>,
+}
+
+impl ShortestPaths {
+    // Walks the predecessor chain back from `to`
+    pub fn path_to(&self, to: NodeIndex) -> Option<(f64, Vec<NodeIndex>)> {
+        let cost = *self.distances.get(&to)?;
+        let mut path = vec![to];
+        let mut current = to;
+        while let Some(&previous) = self.predecessors.get(&current) {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        Some((cost, path))
+    }
+}
+
+// Priority queue entry; BinaryHeap is a max-heap, so the ordering is
+// reversed to pop the cheapest tentative distance first
+#[derive(PartialEq)]
+struct Visit {
+    cost: f64,
+    node: NodeIndex,
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-impl Graph {
+impl<N, E> Graph<N, E> {
     pub fn new() -> Self {
+        Graph::with_kind(GraphKind::Undirected)
+    }
+
+    pub fn with_kind(kind: GraphKind) -> Self {
+        Graph::with_policy(kind, EdgePolicy::Simple)
+    }
+
+    pub fn with_policy(kind: GraphKind, policy: EdgePolicy) -> Self {
         Graph {
-            adjacency_list: HashMap::new(),
+            kind,
+            policy,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            node_count: 0,
+            edge_count: 0,
         }
     }
 
-    pub fn add_node(&mut self, node: String) {
-        self.adjacency_list.entry(node).or_insert(Vec::new());
+    pub fn is_directed(&self) -> bool {
+        self.kind == GraphKind::Directed
     }
 
-    pub fn add_edge(&mut self, from: String, to: String) {
-        // For undirected graph, add edge in both directions
-        self.adjacency_list
-            .entry(from.clone())
-            .or_insert(Vec::new())
-            .push(to.clone());
-        
-        self.adjacency_list
-            .entry(to)
-            .or_insert(Vec::new())
-            .push(from);
+    fn slot(&self, node: NodeIndex) -> Option<&NodeSlot<N>> {
+        self.nodes.get(node.index())?.as_ref()
     }
 
-    pub fn display(&self) {
+    fn slot_mut(&mut self, node: NodeIndex) -> Option<&mut NodeSlot<N>> {
+        self.nodes.get_mut(node.index())?.as_mut()
+    }
+
+    fn edge_slot(&self, edge: EdgeIndex) -> Option<&EdgeSlot<E>> {
+        self.edges.get(edge.index())?.as_ref()
+    }
+
+    pub fn contains_node(&self, node: NodeIndex) -> bool {
+        self.slot(node).is_some()
+    }
+
+    // Handles outlive the nodes they name, so every entry point checks
+    fn check_node(&self, node: NodeIndex) -> Result<NodeIndex, String> {
+        if self.contains_node(node) {
+            Ok(node)
+        } else {
+            Err(format!("no node #{} in the graph", node.index()))
+        }
+    }
+
+    // Live nodes in insertion order, so callers get reproducible output
+    pub fn nodes(&self) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.is_some())
+            .map(|(index, _)| NodeIndex(index as u32))
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeIndex {
+        let node = NodeIndex(u32::try_from(self.nodes.len()).expect("more than u32::MAX nodes"));
+        self.nodes.push(Some(NodeSlot {
+            weight,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        }));
+        self.node_count += 1;
+        node
+    }
+
+    // None once the node has been removed
+    pub fn node_weight(&self, node: NodeIndex) -> Option<&N> {
+        self.slot(node).map(|slot| &slot.weight)
+    }
+
+    pub fn node_weight_mut(&mut self, node: NodeIndex) -> Option<&mut N> {
+        self.slot_mut(node).map(|slot| &mut slot.weight)
+    }
+
+    pub fn edge_weight(&self, edge: EdgeIndex) -> Option<&E> {
+        self.edge_slot(edge).map(|slot| &slot.weight)
+    }
+
+    pub fn edge_weight_mut(&mut self, edge: EdgeIndex) -> Option<&mut E> {
+        self.edges.get_mut(edge.index())?.as_mut().map(|slot| &mut slot.weight)
+    }
+
+    // The ends in the order they were given to add_edge
+    pub fn edge_endpoints(&self, edge: EdgeIndex) -> Option<(NodeIndex, NodeIndex)> {
+        self.edge_slot(edge).map(|slot| (slot.source, slot.target))
+    }
+
+    // First node (in insertion order) whose payload matches; a linear
+    // scan, since payloads are not indexed
+    pub fn find_node(&self, matches: impl Fn(&N) -> bool) -> Option<NodeIndex> {
+        self.nodes()
+            .find(|&node| self.node_weight(node).is_some_and(&matches))
+    }
+
+    // Err for a stale handle, or if a simple graph already has this edge
+    // (or it is a self-loop); the existing edge keeps its payload
+    pub fn add_edge(
+        &mut self,
+        from: NodeIndex,
+        to: NodeIndex,
+        weight: E,
+    ) -> Result<EdgeIndex, String> {
+        self.check_node(from)?;
+        self.check_node(to)?;
+        if self.policy == EdgePolicy::Simple {
+            if from == to {
+                return Err(format!("node #{} cannot link to itself", from.index()));
+            }
+            if self.has_edge(from, to) {
+                return Err(format!("#{} and #{} are already linked", from.index(), to.index()));
+            }
+        }
+        let edge = EdgeIndex(u32::try_from(self.edges.len()).expect("more than u32::MAX edges"));
+        self.edges.push(Some(EdgeSlot {
+            source: from,
+            target: to,
+            weight,
+        }));
+        self.edge_count += 1;
+        self.slot_mut(from).unwrap().outgoing.push(edge);
+
+        let kind = self.kind;
+        let head = self.slot_mut(to).unwrap();
+        match kind {
+            // For undirected graph, list the edge at both ends (a self-loop
+            // twice in its own list)
+            GraphKind::Undirected => head.outgoing.push(edge),
+            // For directed graph, remember the edge at its head as well
+            GraphKind::Directed => head.incoming.push(edge),
+        }
+        Ok(edge)
+    }
+
+    // Undirected edges can be asked for from either end
+    pub fn find_edge(&self, from: NodeIndex, to: NodeIndex) -> Option<EdgeIndex> {
+        self.edges(from)
+            .find(|edge| edge.target == to)
+            .map(|edge| edge.id)
+    }
+
+    pub fn has_edge(&self, from: NodeIndex, to: NodeIndex) -> bool {
+        self.find_edge(from, to).is_some()
+    }
+
+    // Removes one edge (one copy in a multigraph) and returns its payload
+    pub fn remove_edge(&mut self, edge: EdgeIndex) -> Option<E> {
+        let slot = self.edges.get_mut(edge.index())?.take()?;
+        self.edge_count -= 1;
+        for end in [slot.source, slot.target] {
+            // An end may already be gone when remove_node clears its edges
+            if let Some(end) = self.slot_mut(end) {
+                end.outgoing.retain(|&listed| listed != edge);
+                end.incoming.retain(|&listed| listed != edge);
+            }
+        }
+        Some(slot.weight)
+    }
+
+    // Removes the node and every edge touching it and returns the node's
+    // payload, or None if there was no such node
+    pub fn remove_node(&mut self, node: NodeIndex) -> Option<N> {
+        let slot = self.nodes.get_mut(node.index())?.take()?;
+        self.node_count -= 1;
+        // A self-loop is listed twice; the second removal finds nothing
+        for &edge in slot.outgoing.iter().chain(&slot.incoming) {
+            self.remove_edge(edge);
+        }
+        Some(slot.weight)
+    }
+
+    // Each edge in one of `node`'s lists, with the end that is not `node`
+    fn listed<'a>(
+        &'a self,
+        node: NodeIndex,
+        list: fn(&NodeSlot<N>) -> &[EdgeIndex],
+    ) -> impl DoubleEndedIterator<Item = (EdgeIndex, NodeIndex, &'a E)> + 'a {
+        let ids = self.slot(node).map_or(&[][..], list);
+        ids.iter().map(move |&id| {
+            let edge = self.edge_slot(id).expect("listed edges exist");
+            let far = if edge.source == node { edge.target } else { edge.source };
+            (id, far, &edge.weight)
+        })
+    }
+
+    // Edges leaving `node` (every edge touching it when undirected); empty
+    // for an unknown node
+    pub fn edges(&self, node: NodeIndex) -> impl DoubleEndedIterator<Item = EdgeRef<'_, E>> {
+        self.listed(node, |slot| &slot.outgoing)
+            .map(move |(id, target, weight)| EdgeRef {
+                id,
+                source: node,
+                target,
+                weight,
+            })
+    }
+
+    // Edges arriving at `node` (every edge touching it when undirected)
+    fn incoming_edges(&self, node: NodeIndex) -> impl DoubleEndedIterator<Item = EdgeRef<'_, E>> {
+        let list: fn(&NodeSlot<N>) -> &[EdgeIndex] = match self.kind {
+            GraphKind::Directed => |slot| &slot.incoming,
+            GraphKind::Undirected => |slot| &slot.outgoing,
+        };
+        self.listed(node, list).map(move |(id, source, weight)| EdgeRef {
+            id,
+            source,
+            target: node,
+            weight,
+        })
+    }
+
+    // Edges touching the node; an undirected self-loop counts twice
+    pub fn degree(&self, node: NodeIndex) -> usize {
+        match self.kind {
+            GraphKind::Directed => self.in_degree(node) + self.out_degree(node),
+            GraphKind::Undirected => self.out_degree(node),
+        }
+    }
+
+    // Distinct nodes sharing an edge with `node` in either direction
+    pub fn neighbors(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        let mut neighbors: Vec<NodeIndex> = self
+            .edges(node)
+            .map(|edge| edge.target)
+            .chain(self.incoming_edges(node).map(|edge| edge.source))
+            .collect();
+        neighbors.sort();
+        neighbors.dedup();
+        neighbors
+    }
+
+    // Nodes reachable over one edge (every neighbor when undirected)
+    pub fn successors(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        self.edges(node).map(|edge| edge.target).collect()
+    }
+
+    // Nodes with an edge into `node` (every neighbor when undirected)
+    pub fn predecessors(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        self.incoming_edges(node).map(|edge| edge.source).collect()
+    }
+
+    pub fn out_degree(&self, node: NodeIndex) -> usize {
+        self.slot(node).map_or(0, |slot| slot.outgoing.len())
+    }
+
+    pub fn in_degree(&self, node: NodeIndex) -> usize {
+        match self.kind {
+            GraphKind::Directed => self.slot(node).map_or(0, |slot| slot.incoming.len()),
+            GraphKind::Undirected => self.out_degree(node),
+        }
+    }
+
+    // Same nodes and handles with every edge flipped; undirected graphs
+    // come back as is
+    pub fn reverse(&self) -> Graph<N, E>
+    where
+        N: Clone,
+        E: Clone,
+    {
+        let mut reversed = self.clone();
+        if self.is_directed() {
+            for slot in reversed.nodes.iter_mut().flatten() {
+                std::mem::swap(&mut slot.outgoing, &mut slot.incoming);
+            }
+            for edge in reversed.edges.iter_mut().flatten() {
+                std::mem::swap(&mut edge.source, &mut edge.target);
+            }
+        }
+        reversed
+    }
+
+    // One line per node listing the far end of each of its edges,
+    // followed by `describe(payload)` in parentheses when there is one
+    fn structure(&self, describe: impl Fn(&E) -> Option<String>) -> Vec<String>
+    where
+        N: Display,
+    {
+        let arrow = match self.kind {
+            GraphKind::Directed => "→",
+            GraphKind::Undirected => "↔",
+        };
+        self.nodes()
+            .map(|node| {
+                let neighbors: Vec<String> = self
+                    .edges(node)
+                    .map(|edge| {
+                        let far = self.node_weight(edge.target).unwrap();
+                        match describe(edge.weight) {
+                            Some(payload) => format!("{} ({})", far, payload),
+                            None => far.to_string(),
+                        }
+                    })
+                    .collect();
+                format!("{} {} [{}]", self.node_weight(node).unwrap(), arrow, neighbors.join(", "))
+            })
+            .collect()
+    }
+
+    fn show(&self, describe: impl Fn(&E) -> Option<String>)
+    where
+        N: Display,
+    {
         println!("Graph structure:");
-        for (node, neighbors) in &self.adjacency_list {
-            println!("  {} → {:?}", node, neighbors);
+        for line in self.structure(describe) {
+            println!("  {}", line);
         }
     }
 
-    pub fn bfs(&self, start: &str) {
-        let mut visited = HashSet::new();
-        let mut queue = VecDeque::new();
+    // Every edge shown with its payload, rendered by `describe`
+    pub fn display_with(&self, describe: impl Fn(&E) -> String)
+    where
+        N: Display,
+    {
+        self.show(|weight| Some(describe(weight)));
+    }
 
-        queue.push_back(start.to_string());
-        visited.insert(start.to_string());
+    // Cheapest cost from `start` to every reachable node, where
+    // `edge_cost` turns each edge's payload into a cost
+    // O((V + E) log V) with a binary heap; stale heap entries are skipped
+    // rather than decreased in place
+    // Dijkstra relies on costs never going down along a path, so every
+    // cost is checked before the search starts: a negative, NaN or
+    // infinite one is an Err naming the edge, as is a stale start
+    pub fn dijkstra(
+        &self,
+        start: NodeIndex,
+        edge_cost: impl Fn(&E) -> f64,
+    ) -> Result<ShortestPaths, String> {
+        let start = self.check_node(start)?;
+        let mut costs = vec![0.0; self.edges.len()];
+        for (index, slot) in self.edges.iter().enumerate() {
+            let Some(edge) = slot else {
+                continue;
+            };
+            let cost = edge_cost(&edge.weight);
+            if !(cost.is_finite() && cost >= 0.0) {
+                return Err(format!(
+                    "edge #{} (#{} → #{}) costs {}; costs must be finite and non-negative",
+                    index,
+                    edge.source.index(),
+                    edge.target.index(),
+                    cost
+                ));
+            }
+            costs[index] = cost;
+        }
 
-        println!("\nBreadth-First Search from '{}':", start);
-        
-        while let Some(node) = queue.pop_front() {
-            print!("  {} → ", node);
+        let mut distances = HashMap::new();
+        let mut predecessors = HashMap::new();
+        let mut heap = BinaryHeap::new();
 
-            if let Some(neighbors) = self.adjacency_list.get(&node) {
-                for neighbor in neighbors {
-                    if !visited.contains(neighbor) {
-                        visited.insert(neighbor.clone());
-                        queue.push_back(neighbor.clone());
-                    }
+        distances.insert(start, 0.0);
+        heap.push(Visit {
+            cost: 0.0,
+            node: start,
+        });
+
+        while let Some(Visit { cost, node }) = heap.pop() {
+            if cost > distances[&node] {
+                continue; // A cheaper path to this node was already settled
+            }
+            for edge in self.edges(node) {
+                let next_cost = cost + costs[edge.id.index()];
+                let improves = distances
+                    .get(&edge.target)
+                    .is_none_or(|&known| next_cost < known);
+                if improves {
+                    distances.insert(edge.target, next_cost);
+                    predecessors.insert(edge.target, node);
+                    heap.push(Visit {
+                        cost: next_cost,
+                        node: edge.target,
+                    });
                 }
             }
         }
-        println!("End");
+
+        Ok(ShortestPaths {
+            distances,
+            predecessors,
+        })
+    }
+
+    // Cheapest route and its total cost, or None if `to` is unreachable;
+    // Err if either end is not in the graph
+    pub fn shortest_path(
+        &self,
+        from: NodeIndex,
+        to: NodeIndex,
+        edge_cost: impl Fn(&E) -> f64,
+    ) -> Result<Option<(f64, Vec<NodeIndex>)>, String> {
+        let to = self.check_node(to)?;
+        Ok(self.dijkstra(from, edge_cost)?.path_to(to))
+    }
+}
+
+// Edges without a payload show just the far end
+impl<N: Display> Graph<N, ()> {
+    pub fn display(&self) {
+        self.show(|_| None);
+    }
+}
+
+impl<E> Graph<String, E> {
+    // Approximate heap bytes: node slots with their labels and edge
+    // lists, plus the edge slots
+    pub fn heap_bytes(&self) -> usize {
+        let nodes: usize = self
+            .nodes
+            .iter()
+            .flatten()
+            .map(|slot| {
+                let lists = slot.outgoing.capacity() + slot.incoming.capacity();
+                slot.weight.capacity() + lists * size_of::<EdgeIndex>()
+            })
+            .sum();
+        self.nodes.capacity() * size_of::<Option<NodeSlot<String>>>()
+            + nodes
+            + self.edges.capacity() * size_of::<Option<EdgeSlot<E>>>()
+    }
+}
+
+// Node payload for the piping model: one piece of plant equipment
+#[derive(Debug)]
+struct Equipment {
+    tag: &'static str,
+    service: &'static str,
+    online: bool,
+}
+
+impl Display for Equipment {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.tag)
+    }
+}
+
+// Edge payload: the pipe run joining two units
+#[derive(Debug)]
+struct Pipe {
+    diameter_mm: f64,
+    length_m: f64,
+    material: &'static str,
+}
+
+impl Pipe {
+    // Relative pressure drop at a fixed flow: by Darcy-Weisbach it grows
+    // with length and with the inverse fifth power of the bore
+    fn pressure_drop(&self) -> f64 {
+        self.length_m * (100.0 / self.diameter_mm).powi(5)
+    }
+}
+
+// Adds one node per label, remembering each handle by its label so that
+// edges can name their ends without scanning the graph
+fn add_labelled<'a, E>(
+    graph: &mut Graph<String, E>,
+    names: &[&'a str],
+) -> HashMap<&'a str, NodeIndex> {
+    names
+        .iter()
+        .map(|&name| (name, graph.add_node(name.to_string())))
+        .collect()
+}
+
+fn label<E>(graph: &Graph<String, E>, node: NodeIndex) -> &str {
+    graph.node_weight(node).expect("handle from this graph")
+}
+
+fn labels<'a, E>(graph: &'a Graph<String, E>, nodes: &[NodeIndex]) -> Vec<&'a str> {
+    nodes.iter().map(|&node| label(graph, node)).collect()
+}
+
+// Undirected links written as "A ↔ B"
+fn links<E>(graph: &Graph<String, E>, edges: &[EdgeIndex]) -> Vec<String> {
+    edges
+        .iter()
+        .map(|&edge| {
+            let (a, b) = graph.edge_endpoints(edge).expect("edge is in the graph");
+            format!("{} ↔ {}", label(graph, a), label(graph, b))
+        })
+        .collect()
+}
+
+// Custom DFS algorithm built from visitor hooks: prints the nesting of
+// the search as parentheses and finds the pipe with the worst pressure drop
+struct FlowAudit<'g> {
+    graph: &'g Graph<Equipment, Pipe>,
+    trace: Vec<String>,
+    edges_examined: usize,
+    worst: Option<(NodeIndex, NodeIndex, f64)>,
+}
+
+impl<'g> FlowAudit<'g> {
+    fn new(graph: &'g Graph<Equipment, Pipe>) -> Self {
+        FlowAudit {
+            graph,
+            trace: Vec::new(),
+            edges_examined: 0,
+            worst: None,
+        }
+    }
+}
+
+impl Visitor<Pipe> for FlowAudit<'_> {
+    fn discover(&mut self, step: &Step) {
+        let equipment = self.graph.node_weight(step.node).expect("handle from this graph");
+        self.trace.push(format!("({}", equipment.tag));
+    }
+
+    fn examine_edge(&mut self, edge: &EdgeRef<Pipe>) {
+        self.edges_examined += 1;
+        let drop = edge.weight.pressure_drop();
+        if self.worst.is_none_or(|(_, _, worst)| drop > worst) {
+            self.worst = Some((edge.source, edge.target, drop));
+        }
+    }
+
+    fn finish(&mut self, _node: NodeIndex) {
+        self.trace.push(")".to_string());
     }
 }
 
 pub fn demonstrate() {
     println!("Graph: Network of connected nodes (vertices and edges)\n");
 
-    // Example: Process plant equipment network
+    // Example: Process plant equipment network with plain string labels;
+    // equipment and pipe data live in the typed example further down
     println!("Building process plant equipment network:");
-    let mut plant_network = Graph::new();
+    let mut plant_network: Graph<String, ()> = Graph::new();
 
     // Add equipment nodes
-    let equipment = vec![
-        "Reactor",
-        "Heat Exchanger",
-        "Pump-A",
-        "Pump-B",
-        "Storage Tank",
-        "Control Valve",
-        "Separator",
-    ];
-
-    for item in &equipment {
-        plant_network.add_node(item.to_string());
-        println!("  Added: {}", item);
+    let plant = add_labelled(&mut plant_network, &PLANT_EQUIPMENT);
+    for item in PLANT_EQUIPMENT {
+        println!("  Added: {} (node #{})", item, plant[item].index());
     }
 
     // Define connections (process flow)
     println!("\nAdding connections (process flow):");
-    let connections = vec![
+    let connections = [
         ("Storage Tank", "Pump-A"),
         ("Pump-A", "Heat Exchanger"),
         ("Heat Exchanger", "Reactor"),
@@ -104,32 +725,782 @@ pub fn demonstrate() {
     ];
 
     for (from, to) in connections {
-        plant_network.add_edge(from.to_string(), to.to_string());
+        plant_network
+            .add_edge(plant[from], plant[to], ())
+            .expect("units are in the network");
         println!("  {} ↔ {}", from, to);
     }
 
     println!();
     plant_network.display();
 
+    // A simple graph keeps one pipe per pair of units
+    let repeated = match plant_network.add_edge(plant["Pump-A"], plant["Storage Tank"], ()) {
+        Ok(_) => "added".to_string(),
+        Err(err) => format!("rejected, {}", err),
+    };
+    println!(
+        "\n  Adding Pump-A ↔ Storage Tank again: {} ({} units, {} pipes)",
+        repeated,
+        plant_network.node_count(),
+        plant_network.edge_count()
+    );
+
     // Perform BFS to show reachability
-    plant_network.bfs("Reactor");
+    println!("\nBreadth-First Search from 'Reactor':");
+    let reactor = plant["Reactor"];
+    for step in plant_network.bfs_iter(reactor).expect("Reactor is in the network") {
+        println!(
+            "  {}{} (depth {}, from {})",
+            "  ".repeat(step.depth),
+            label(&plant_network, step.node),
+            step.depth,
+            step.parent.map_or("start", |parent| label(&plant_network, parent))
+        );
+    }
+    println!(
+        "  Boiler in the network? {}",
+        plant_network.find_node(|unit| unit == "Boiler").is_some()
+    );
+    let tank = plant["Storage Tank"];
+    if let Ok(Some(hops)) = plant_network.shortest_hop_path(tank, reactor) {
+        println!(
+            "  Fewest hops Storage Tank → Reactor: {} ({} hops)",
+            labels(&plant_network, &hops).join(" → "),
+            hops.len() - 1
+        );
+    }
 
-    // Another example: Communication network
-    println!("\n--- Sensor Communication Network ---");
-    let mut sensor_network = Graph::new();
+    // The connections are really process flow, which only goes one way
+    println!("\n--- Process Flow (directed) ---");
+    let mut process_flow: Graph<String, ()> = Graph::with_kind(GraphKind::Directed);
+    // Handles belong to the graph that issued them, so look units up here
+    let flow = add_labelled(&mut process_flow, &PLANT_EQUIPMENT);
+    let (flow_tank, flow_reactor) = (flow["Storage Tank"], flow["Reactor"]);
+    for (from, to) in connections {
+        process_flow
+            .add_edge(flow[from], flow[to], ())
+            .expect("units are in the network");
+    }
+    process_flow.display();
+    println!("\nFlow through each unit:");
+    for unit in ["Heat Exchanger", "Control Valve", "Storage Tank"] {
+        let node = flow[unit];
+        println!(
+            "  {}: in {} / out {}, from {:?}, to {:?}",
+            unit,
+            process_flow.in_degree(node),
+            process_flow.out_degree(node),
+            labels(&process_flow, &process_flow.predecessors(node)),
+            labels(&process_flow, &process_flow.successors(node))
+        );
+    }
+    if let Ok(Some(hops)) = process_flow.shortest_hop_path(flow_tank, flow_reactor) {
+        println!(
+            "  Storage Tank → Reactor with the flow: {} ({} hops)",
+            labels(&process_flow, &hops).join(" → "),
+            hops.len() - 1
+        );
+    }
+
+    println!("\nDepth-first walk along the flow from Storage Tank:");
+    let walk: Vec<String> = process_flow
+        .dfs_iter(flow_tank)
+        .expect("Storage Tank is in the network")
+        .map(|step| format!("{}@{}", label(&process_flow, step.node), step.depth))
+        .collect();
+    println!("  {}", walk.join(", "));
 
-    sensor_network.add_node("Central Hub".to_string());
-    sensor_network.add_node("Sensor-A".to_string());
-    sensor_network.add_node("Sensor-B".to_string());
-    sensor_network.add_node("Sensor-C".to_string());
-    sensor_network.add_node("Sensor-D".to_string());
+    // Reversing the graph turns "downstream of" into "upstream of"
+    let upstream = process_flow.reverse();
+    let separator = flow["Separator"];
+    let feeds_separator: Vec<NodeIndex> = upstream
+        .reachable_from(separator)
+        .expect("Separator is in the network")
+        .into_iter()
+        .filter(|&unit| unit != separator)
+        .collect();
+    println!(
+        "  Units upstream of Separator (reversed graph): {:?}",
+        labels(&upstream, &feeds_separator)
+    );
+    println!(
+        "  Directed? flow {}, pipes {}, reversed pipes still undirected? {}",
+        process_flow.is_directed(),
+        plant_network.is_directed(),
+        !plant_network.reverse().is_directed()
+    );
+
+    // Startup order: every unit after the units feeding it
+    println!("\n--- Startup Order (topological sort) ---");
+    let describe = |result: Result<Vec<NodeIndex>, OrderError>| match result {
+        Ok(order) => format!("order {}", labels(&process_flow, &order).join(" → ")),
+        Err(OrderError::Cycle(cycle)) => {
+            format!("no order, cycle {}", labels(&process_flow, &cycle).join(" → "))
+        }
+        Err(OrderError::Undirected) => "no order, the links have no direction".to_string(),
+    };
+    println!("  Kahn: {}", describe(process_flow.topological_sort()));
+    println!("  DFS:  {}", describe(process_flow.topological_sort_dfs()));
+    println!("  Pipe network: {}", describe(plant_network.topological_sort()));
+
+    // The recycle line from Pump-B back to the tank stays shut during startup
+    let mut startup = process_flow.clone();
+    let recycle = startup.find_edge(flow["Pump-B"], flow_tank).expect("recycle line exists");
+    startup.remove_edge(recycle).expect("recycle line exists");
+    println!("  Recycle line Pump-B → Storage Tank closed:");
+    for (name, result) in [
+        ("Kahn", startup.topological_sort()),
+        ("DFS ", startup.topological_sort_dfs()),
+    ] {
+        let order = result.expect("no cycle once the recycle line is closed");
+        println!("    {}: {}", name, labels(&startup, &order).join(" → "));
+    }
+    let after_reactor: Vec<NodeIndex> = startup
+        .reachable_from(flow_reactor)
+        .expect("Reactor is in the network")
+        .into_iter()
+        .filter(|&unit| unit != flow_reactor)
+        .collect();
+    println!("  Start only once Reactor is up: {:?}", labels(&startup, &after_reactor));
+    println!(
+        "  Cycles? flow {}, startup {}, pipe network (undirected) {}",
+        process_flow.has_cycle(),
+        startup.has_cycle(),
+        plant_network.has_cycle()
+    );
 
-    sensor_network.add_edge("Central Hub".to_string(), "Sensor-A".to_string());
-    sensor_network.add_edge("Central Hub".to_string(), "Sensor-B".to_string());
-    sensor_network.add_edge("Sensor-A".to_string(), "Sensor-C".to_string());
-    sensor_network.add_edge("Sensor-B".to_string(), "Sensor-D".to_string());
+    // Loops in the flow: units that can all reach each other
+    println!("\nStrongly connected components:");
+    let loops: Vec<Vec<&str>> = process_flow
+        .strongly_connected_components()
+        .iter()
+        .map(|component| labels(&process_flow, component))
+        .collect();
+    println!("  Flow:    {:?}", loops);
+    println!("  Startup: {} singletons", startup.strongly_connected_components().len());
+
+    // Another example: Communication network, links without a payload
+    println!("\n--- Sensor Communication Network ---");
+    let mut sensor_network: Graph<String, ()> = Graph::new();
+    let sensors = add_labelled(&mut sensor_network, &SENSOR_NETWORK);
+
+    for (from, to) in [
+        ("Central Hub", "Sensor-A"),
+        ("Central Hub", "Sensor-B"),
+        ("Sensor-A", "Sensor-C"),
+        ("Sensor-B", "Sensor-D"),
+    ] {
+        sensor_network
+            .add_edge(sensors[from], sensors[to], ())
+            .expect("sensors are in the network");
+    }
 
     println!();
     sensor_network.display();
-    sensor_network.bfs("Central Hub");
+    let hub = sensors["Central Hub"];
+    let hops: Vec<String> = sensor_network
+        .bfs_iter(hub)
+        .expect("Central Hub is in the network")
+        .skip(1)
+        .map(|step| format!("{} {}", label(&sensor_network, step.node), step.depth))
+        .collect();
+    println!("\nHops from Central Hub: {}", hops.join(", "));
+    let relay = sensor_network
+        .shortest_hop_path(sensors["Sensor-C"], sensors["Sensor-D"])
+        .expect("both sensors are in the network")
+        .expect("the sensors are connected");
+    println!("  Sensor-C → Sensor-D: {:?}", labels(&sensor_network, &relay));
+    println!("  Any loops? {} (a tree of links)", sensor_network.has_cycle());
+
+    // Which single failure cuts sensors off from the hub?
+    println!("\nResilience check:");
+    println!(
+        "  Sensor network single points of failure: {:?}",
+        labels(&sensor_network, &sensor_network.articulation_points())
+    );
+    println!(
+        "  Links with no backup: {:?}",
+        links(&sensor_network, &sensor_network.bridges())
+    );
+    println!(
+        "  Pipe network: cut points {:?}, bridges {:?} (the loops give every unit two routes)",
+        labels(&plant_network, &plant_network.articulation_points()),
+        links(&plant_network, &plant_network.bridges())
+    );
+    sensor_network.add_node("Sensor-E".to_string());
+    let groups: Vec<Vec<&str>> = sensor_network
+        .connected_components()
+        .iter()
+        .map(|component| labels(&sensor_network, component))
+        .collect();
+    println!("  With Sensor-E installed but not yet linked: {:?}", groups);
+
+    // Taking a unit out for service removes its pipes too; its handle
+    // stops working, while every other handle stays valid
+    let mut maintenance = plant_network.clone();
+    let pump_a = plant["Pump-A"];
+    let disconnected = maintenance.degree(pump_a);
+    let removed = maintenance.remove_node(pump_a).expect("Pump-A is in the network");
+    println!(
+        "  {} out for service: {} pipes disconnected, {} units / {} pipes left",
+        removed,
+        disconnected,
+        maintenance.node_count(),
+        maintenance.edge_count()
+    );
+    println!(
+        "    New single points of failure: {:?}",
+        labels(&maintenance, &maintenance.articulation_points())
+    );
+    println!(
+        "    Storage Tank ↔ Pump-A still listed? {}, Storage Tank degree {}, neighbors {:?}",
+        maintenance.has_edge(tank, pump_a),
+        maintenance.degree(tank),
+        labels(&maintenance, &maintenance.neighbors(tank))
+    );
+    if let Err(error) = maintenance.bfs_iter(pump_a) {
+        println!("    BFS from the old Pump-A handle: {}", error);
+    }
+    if let Err(error) = maintenance.add_edge(pump_a, tank, ()) {
+        println!("    Relinking the old Pump-A handle: {}", error);
+    }
+
+    // A multigraph counts a backup radio link next to the cable on purpose
+    let mut redundant: Graph<String, ()> =
+        Graph::with_policy(GraphKind::Undirected, EdgePolicy::Multigraph);
+    let linked = add_labelled(&mut redundant, &SENSOR_NETWORK[..4]);
+    for (from, to) in [
+        ("Central Hub", "Sensor-A"),
+        ("Central Hub", "Sensor-A"),
+        ("Central Hub", "Sensor-B"),
+        ("Sensor-A", "Sensor-C"),
+    ] {
+        redundant
+            .add_edge(linked[from], linked[to], ())
+            .expect("multigraphs accept repeated links");
+    }
+    let (hub, sensor_a) = (linked["Central Hub"], linked["Sensor-A"]);
+    println!(
+        "  Multigraph with a backup Hub ↔ Sensor-A link: {} links, hub degree {}, bridges {:?}",
+        redundant.edge_count(),
+        redundant.degree(hub),
+        links(&redundant, &redundant.bridges())
+    );
+    let cable = redundant.find_edge(sensor_a, hub).expect("Hub and Sensor-A are linked");
+    redundant.remove_edge(cable);
+    println!(
+        "    One Hub ↔ Sensor-A link cut: {} links left, still connected? {}",
+        redundant.edge_count(),
+        redundant.has_edge(hub, sensor_a)
+    );
+
+    // Real payloads: equipment records on the nodes, pipe specs on the edges
+    println!("\n--- Equipment and Pipe Payloads ---");
+    let mut piping: Graph<Equipment, Pipe> = Graph::with_kind(GraphKind::Directed);
+    let services = [
+        "reaction",
+        "heating",
+        "feed",
+        "recycle",
+        "storage",
+        "flow control",
+        "separation",
+    ];
+    let mut units: HashMap<&str, NodeIndex> = HashMap::new();
+    for (tag, service) in PLANT_EQUIPMENT.into_iter().zip(services) {
+        let equipment = Equipment {
+            tag,
+            service,
+            online: true,
+        };
+        units.insert(tag, piping.add_node(equipment));
+    }
+    for (from, to, diameter_mm, length_m, material) in [
+        ("Storage Tank", "Pump-A", 150.0, 8.0, "carbon steel"),
+        ("Pump-A", "Heat Exchanger", 100.0, 14.0, "carbon steel"),
+        ("Heat Exchanger", "Reactor", 50.0, 6.0, "stainless steel"),
+        ("Reactor", "Separator", 100.0, 10.0, "stainless steel"),
+        ("Separator", "Control Valve", 80.0, 5.0, "carbon steel"),
+        ("Control Valve", "Pump-B", 80.0, 4.0, "carbon steel"),
+        ("Pump-B", "Storage Tank", 100.0, 12.0, "PVC"),
+        ("Heat Exchanger", "Control Valve", 40.0, 9.0, "carbon steel"),
+    ] {
+        let pipe = Pipe {
+            diameter_mm,
+            length_m,
+            material,
+        };
+        piping.add_edge(units[from], units[to], pipe).expect("units are in the plant");
+    }
+
+    piping.display_with(|pipe| format!("{} mm, {} m", pipe.diameter_mm, pipe.length_m));
+    let tag = |graph: &Graph<Equipment, Pipe>, node| graph.node_weight(node).unwrap().tag;
+    let exchanger = units["Heat Exchanger"];
+    for edge in piping.edges(exchanger) {
+        let pipe = edge.weight;
+        println!(
+            "  {} → {}: {} mm {}, {} m",
+            tag(&piping, edge.source),
+            tag(&piping, edge.target),
+            pipe.diameter_mm,
+            pipe.material,
+            pipe.length_m
+        );
+    }
+
+    // The shortest pipe run is not the one with the least pressure drop
+    let (pump_a, pump_b) = (units["Pump-A"], units["Pump-B"]);
+    let route = |graph: &Graph<Equipment, Pipe>, cost: fn(&Pipe) -> f64| {
+        let (total, path) = graph
+            .shortest_path(pump_a, pump_b, cost)
+            .expect("pipe costs are finite and non-negative")
+            .expect("Pump-B is downstream");
+        let units: Vec<String> = path
+            .iter()
+            .map(|&node| {
+                let equipment = graph.node_weight(node).unwrap();
+                format!("{} ({})", equipment.tag, equipment.service)
+            })
+            .collect();
+        format!("{} [{:.1}]", units.join(" → "), total)
+    };
+    println!("  Pump-A → Pump-B by length (m): {}", route(&piping, |pipe| pipe.length_m));
+    println!("  By pressure drop: {}", route(&piping, Pipe::pressure_drop));
+
+    // Cheapest pressure drop from the tank to every unit it feeds
+    println!("\nLowest pressure-drop routes from Storage Tank (Dijkstra):");
+    let storage = units["Storage Tank"];
+    // A pressure gain is not a usable cost: rejected before the search
+    if let Err(err) = piping.dijkstra(storage, |pipe| -pipe.pressure_drop()) {
+        println!("  Negated pressure drops rejected: {}", err);
+    }
+    let from_tank = piping
+        .dijkstra(storage, Pipe::pressure_drop)
+        .expect("pressure drops are finite and non-negative");
+    let mut by_cost: Vec<(NodeIndex, f64)> =
+        from_tank.distances.iter().map(|(&node, &cost)| (node, cost)).collect();
+    by_cost.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    for (node, cost) in by_cost {
+        let via = from_tank
+            .predecessors
+            .get(&node)
+            .map_or("start", |&previous| tag(&piping, previous));
+        println!("  {:<15} {:>7.1} (via {})", tag(&piping, node), cost, via);
+    }
+
+    let mut audit = FlowAudit::new(&piping);
+    piping
+        .depth_first_visit(storage, &mut audit)
+        .expect("Storage Tank is in the plant");
+    println!("  Visitor trace: {}", audit.trace.join(""));
+    if let Some((from, to, drop)) = audit.worst {
+        println!(
+            "  {} pipes examined, worst {} → {} (pressure drop {:.1})",
+            audit.edges_examined,
+            tag(&piping, from),
+            tag(&piping, to),
+            drop
+        );
+    }
+
+    // Mutable access: rebore the narrow bypass and take the reactor offline
+    let control_valve = units["Control Valve"];
+    let bypass = piping.find_edge(exchanger, control_valve).expect("bypass line exists");
+    let pipe = piping.edge_weight_mut(bypass).expect("bypass line exists");
+    pipe.diameter_mm = 80.0;
+    pipe.material = "stainless steel";
+    let reactor = units["Reactor"];
+    piping.node_weight_mut(reactor).expect("Reactor is in the plant").online = false;
+    let rebored = piping.edge_weight(bypass).expect("bypass line exists");
+    println!(
+        "  Bypass rebored to {} mm {}: {}",
+        rebored.diameter_mm,
+        rebored.material,
+        route(&piping, Pipe::pressure_drop)
+    );
+    let offline: Vec<&str> = piping
+        .nodes()
+        .filter_map(|node| piping.node_weight(node))
+        .filter(|equipment| !equipment.online)
+        .map(|equipment| equipment.tag)
+        .collect();
+    println!("  Offline for inspection: {:?}", offline);
+
+    // Labels become small integer IDs; algorithms then run on the IDs
+    println!("\n--- Interned Node IDs (large plant models) ---");
+    let mut compact = InternedGraph::new(GraphKind::Directed);
+    for (from, to) in connections {
+        compact.add_edge(from, to);
+    }
+    let exchanger = compact.id("Heat Exchanger").expect("Heat Exchanger was interned");
+    let downstream: Vec<&str> = compact
+        .neighbors(exchanger)
+        .iter()
+        .map(|&id| compact.label(id))
+        .collect();
+    println!(
+        "  {} units, {} links; Heat Exchanger is node #{}, flows to {:?}; Boiler has an ID? {}",
+        compact.node_count(),
+        compact.edge_count(),
+        exchanger.index(),
+        downstream,
+        compact.id("Boiler").is_some()
+    );
+    let frozen = compact.to_csr();
+    let storage = compact.id("Storage Tank").expect("Storage Tank was interned");
+    let order: Vec<&str> = frozen.bfs(storage).into_iter().map(|id| compact.label(id)).collect();
+    println!("  CSR BFS from Storage Tank: {}", order.join(" → "));
+    println!("  Same reach without freezing? {}", compact.bfs(storage) == frozen.bfs(storage));
+
+    // Heap footprint of the plant in each representation; the timed
+    // comparison on a large synthetic plant is `cargo bench --bench graph_reach`
+    println!(
+        "  Heap: Graph<String, _> {} B, interned {} B, CSR +{} B",
+        plant_network.heap_bytes(),
+        compact.heap_bytes(),
+        frozen.heap_bytes()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    //   a --1--> b --2--> d
+    //   |                 ^
+    //   +----4---> c --0--+      e (isolated)
+    fn routes() -> (Graph<&'static str, f64>, [NodeIndex; 5]) {
+        let mut graph = Graph::with_kind(GraphKind::Directed);
+        let [a, b, c, d, e] = ["a", "b", "c", "d", "e"].map(|name| graph.add_node(name));
+        for (from, to, cost) in [(a, b, 1.0), (b, d, 2.0), (a, c, 4.0), (c, d, 0.0)] {
+            graph.add_edge(from, to, cost).unwrap();
+        }
+        (graph, [a, b, c, d, e])
+    }
+
+    #[test]
+    fn dijkstra_finds_cheapest_routes() {
+        let (graph, [a, b, c, d, e]) = routes();
+        let paths = graph.dijkstra(a, |cost| *cost).unwrap();
+        assert_eq!(paths.distances.len(), 4);
+        let distance = |node| paths.distances[&node];
+        assert_eq!((distance(b), distance(c), distance(d)), (1.0, 4.0, 3.0));
+        assert_eq!(paths.path_to(d), Some((3.0, vec![a, b, d])));
+        assert_eq!(paths.path_to(e), None);
+        assert_eq!(graph.shortest_path(d, a, |cost| *cost), Ok(None));
+    }
+
+    #[test]
+    fn dijkstra_rejects_unusable_costs() {
+        let (graph, [a, _, _, d, e]) = routes();
+        for bad in [-1.0, f64::NAN, f64::INFINITY] {
+            let result = graph.dijkstra(a, |cost| if *cost == 2.0 { bad } else { *cost });
+            let err = result.unwrap_err();
+            assert!(err.starts_with("edge #1 (#1 → #3) costs"), "{}", err);
+            assert!(graph.shortest_path(a, d, |_| bad).is_err());
+        }
+        // Checked up front, even from a start that reaches nothing
+        assert!(graph.shortest_path(e, a, |cost| -cost).is_err());
+    }
+
+    #[test]
+    fn searches_reject_unknown_ends() {
+        let (mut graph, [a, b, c, d, _]) = routes();
+        let cost = |cost: &f64| *cost;
+        let unknown = NodeIndex(99);
+        let missing = "no node #99 in the graph";
+        assert_eq!(graph.dijkstra(unknown, cost).unwrap_err(), missing);
+        assert_eq!(graph.shortest_path(unknown, d, cost).unwrap_err(), missing);
+        assert_eq!(graph.shortest_path(a, unknown, cost).unwrap_err(), missing);
+
+        // A removed node's handle is stale, not an empty search
+        graph.remove_node(b);
+        let stale = "no node #1 in the graph";
+        assert_eq!(graph.dijkstra(b, cost).unwrap_err(), stale);
+        assert_eq!(graph.shortest_path(b, d, cost).unwrap_err(), stale);
+        assert_eq!(graph.shortest_path(a, b, cost).unwrap_err(), stale);
+        assert_eq!(graph.shortest_path(a, d, cost), Ok(Some((4.0, vec![a, c, d]))));
+    }
+
+    #[test]
+    fn directed_degrees_count_each_direction() {
+        let mut flow = Graph::with_policy(GraphKind::Directed, EdgePolicy::Multigraph);
+        let [tank, pump, valve] = ["tank", "pump", "valve"].map(|name| flow.add_node(name));
+        for (from, to) in [(tank, pump), (pump, valve), (tank, valve), (valve, valve)] {
+            flow.add_edge(from, to, ()).unwrap();
+        }
+        let counts = |node| (flow.in_degree(node), flow.out_degree(node), flow.degree(node));
+        assert_eq!(counts(tank), (0, 2, 2));
+        assert_eq!(counts(pump), (1, 1, 2));
+        // The self-loop leaves and arrives, so it counts on both sides
+        assert_eq!(counts(valve), (3, 1, 4));
+        assert_eq!(flow.successors(valve), [valve]);
+        assert_eq!(flow.predecessors(valve), [pump, tank, valve]);
+        assert_eq!(flow.neighbors(valve), [tank, pump, valve]);
+        assert_eq!(flow.successors(tank), [pump, valve]);
+        assert!(flow.predecessors(tank).is_empty());
+
+        // Undirected, a self-loop is listed twice at its only end
+        let mut pipes = Graph::with_policy(GraphKind::Undirected, EdgePolicy::Multigraph);
+        let [tank, pump] = ["tank", "pump"].map(|name| pipes.add_node(name));
+        pipes.add_edge(tank, pump, ()).unwrap();
+        pipes.add_edge(pump, pump, ()).unwrap();
+        assert_eq!(pipes.successors(pump), [tank, pump, pump]);
+        assert_eq!(pipes.predecessors(pump), pipes.successors(pump));
+        assert_eq!((pipes.in_degree(pump), pipes.out_degree(pump), pipes.degree(pump)), (3, 3, 3));
+        assert_eq!(pipes.neighbors(pump), [tank, pump]);
+    }
+
+    #[test]
+    fn reverse_flips_every_directed_edge() {
+        let (graph, nodes) = routes();
+        let reversed = graph.reverse();
+        assert_eq!((reversed.node_count(), reversed.edge_count()), (5, 4));
+        for node in nodes {
+            assert_eq!(reversed.successors(node), graph.predecessors(node));
+            assert_eq!(reversed.predecessors(node), graph.successors(node));
+            assert_eq!(reversed.in_degree(node), graph.out_degree(node));
+        }
+        let [a, b, ..] = nodes;
+        let ab = graph.find_edge(a, b).unwrap();
+        assert_eq!(reversed.edge_endpoints(ab), Some((b, a)));
+        assert_eq!(reversed.find_edge(b, a), Some(ab));
+        assert_eq!(reversed.reverse().successors(a), graph.successors(a));
+
+        let mut pipes = Graph::new();
+        let [tank, pump] = ["tank", "pump"].map(|name| pipes.add_node(name));
+        let pipe = pipes.add_edge(tank, pump, ()).unwrap();
+        let same = pipes.reverse();
+        assert!(!same.is_directed());
+        assert_eq!(same.edge_endpoints(pipe), Some((tank, pump)));
+        assert_eq!((same.successors(tank), same.successors(pump)), (vec![pump], vec![tank]));
+    }
+
+    #[test]
+    fn display_shows_direction_and_payloads() {
+        let (graph, _) = routes();
+        let lines = graph.structure(|cost| Some(format!("{:.1}", cost)));
+        assert_eq!((&*lines[0], &*lines[3]), ("a → [b (1.0), c (4.0)]", "d → []"));
+        assert_eq!(graph.structure(|_| None)[0], "a → [b, c]");
+
+        let mut pipes = Graph::new();
+        let [tank, pump, valve] = ["tank", "pump", "valve"].map(|name| pipes.add_node(name));
+        pipes.add_edge(tank, pump, ()).unwrap();
+        pipes.add_edge(valve, pump, ()).unwrap();
+        let lines = ["tank ↔ [pump]", "pump ↔ [tank, valve]", "valve ↔ [pump]"];
+        assert_eq!(pipes.structure(|_| None), lines);
+    }
+
+    // Every live edge is listed exactly where its kind says (a self-loop
+    // twice in its node's only list when undirected), nothing else is
+    // listed, and the counters match the live slots
+    fn lists_agree<N, E>(graph: &Graph<N, E>) {
+        let live_nodes = graph.nodes.iter().flatten().count();
+        let live_edges = graph.edges.iter().flatten().count();
+        assert_eq!((graph.node_count(), graph.edge_count()), (live_nodes, live_edges));
+        let listings: usize = graph
+            .nodes
+            .iter()
+            .flatten()
+            .map(|slot| slot.outgoing.len() + slot.incoming.len())
+            .sum();
+        assert_eq!(listings, 2 * live_edges);
+        let times = |list: &[EdgeIndex], id| list.iter().filter(|&&listed| listed == id).count();
+        for (index, edge) in graph.edges.iter().enumerate() {
+            let Some(edge) = edge else {
+                continue;
+            };
+            let id = EdgeIndex(index as u32);
+            let (source, target) = (graph.slot(edge.source), graph.slot(edge.target));
+            let (source, target) = (source.expect("live edge end"), target.expect("live edge end"));
+            match (graph.kind, edge.source == edge.target) {
+                (GraphKind::Undirected, true) => assert_eq!(times(&source.outgoing, id), 2),
+                (GraphKind::Undirected, false) => {
+                    assert_eq!((times(&source.outgoing, id), times(&target.outgoing, id)), (1, 1));
+                }
+                (GraphKind::Directed, _) => {
+                    assert_eq!((times(&source.outgoing, id), times(&target.incoming, id)), (1, 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn remove_node_detaches_every_incident_edge() {
+        for kind in [GraphKind::Directed, GraphKind::Undirected] {
+            let mut graph = Graph::with_policy(kind, EdgePolicy::Multigraph);
+            let [a, b, c] = ["a", "b", "c"].map(|name| graph.add_node(name));
+            let links = [(a, b), (b, a), (b, c), (c, b), (b, b), (b, b), (a, c)];
+            let edges = links.map(|(from, to)| graph.add_edge(from, to, ()).unwrap());
+            lists_agree(&graph);
+
+            assert_eq!(graph.remove_node(b), Some("b"));
+            lists_agree(&graph);
+            assert_eq!((graph.node_count(), graph.edge_count()), (2, 1));
+            assert_eq!((graph.degree(a), graph.degree(c)), (1, 1));
+            assert_eq!((graph.successors(a), graph.predecessors(c)), (vec![c], vec![a]));
+            assert!(graph.predecessors(a).iter().all(|&node| node == c));
+            assert_eq!((graph.find_edge(a, b), graph.find_edge(c, b)), (None, None));
+            for &edge in &edges[..6] {
+                assert_eq!(graph.edge_endpoints(edge), None);
+                assert_eq!(graph.remove_edge(edge), None);
+            }
+            assert_eq!(graph.edge_endpoints(edges[6]), Some((a, c)));
+
+            // Stale handles stay stale: nothing removed is handed out again
+            assert_eq!((graph.remove_node(b), graph.degree(b)), (None, 0));
+            let d = graph.add_node("d");
+            assert_eq!(d.index(), 3);
+            assert_eq!(graph.node_weight(b), None);
+            let edge = graph.add_edge(a, d, ()).unwrap();
+            assert_eq!(edge.index(), 7);
+            lists_agree(&graph);
+        }
+    }
+
+    // Random node and edge removals never leave a dangling listing, and
+    // every handle issued is new
+    #[test]
+    fn random_removals_keep_lists_consistent() {
+        let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        for kind in [GraphKind::Directed, GraphKind::Undirected] {
+            let mut graph = Graph::with_policy(kind, EdgePolicy::Multigraph);
+            let mut nodes: Vec<NodeIndex> = Vec::new();
+            let mut edges: Vec<EdgeIndex> = Vec::new();
+            let mut issued = HashSet::new();
+            for step in 0..2000 {
+                match next() % 5 {
+                    0 | 1 if nodes.len() > 1 => {
+                        let from = nodes[next() % nodes.len()];
+                        let to = nodes[next() % nodes.len()];
+                        let edge = graph.add_edge(from, to, step).unwrap();
+                        assert!(issued.insert(("edge", edge.index())));
+                        edges.push(edge);
+                    }
+                    2 if !edges.is_empty() => {
+                        let edge = edges.swap_remove(next() % edges.len());
+                        assert!(graph.remove_edge(edge).is_some());
+                    }
+                    3 if !nodes.is_empty() => {
+                        let node = nodes.swap_remove(next() % nodes.len());
+                        let touches = |edge: &EdgeIndex| {
+                            let (from, to) = graph.edge_endpoints(*edge).unwrap();
+                            from == node || to == node
+                        };
+                        let touching = edges.iter().filter(|edge| touches(edge)).count();
+                        let before = graph.edge_count();
+                        assert!(graph.remove_node(node).is_some());
+                        assert_eq!(graph.edge_count(), before - touching);
+                        edges.retain(|&edge| graph.edge_endpoints(edge).is_some());
+                        assert_eq!(edges.len(), graph.edge_count());
+                    }
+                    _ => {
+                        let node = graph.add_node(step);
+                        assert!(issued.insert(("node", node.index())));
+                        nodes.push(node);
+                    }
+                }
+                lists_agree(&graph);
+            }
+        }
+    }
+
+    #[test]
+    fn add_edge_rejects_stale_handles_and_repeats() {
+        let (mut graph, [a, b, _, d, _]) = routes();
+        assert_eq!(graph.add_edge(a, b, 9.0), Err("#0 and #1 are already linked".to_string()));
+        assert_eq!(graph.add_edge(d, d, 9.0), Err("node #3 cannot link to itself".to_string()));
+        graph.remove_node(b);
+        assert_eq!(graph.add_edge(a, b, 9.0), Err("no node #1 in the graph".to_string()));
+        assert_eq!(graph.add_edge(b, d, 9.0), Err("no node #1 in the graph".to_string()));
+        assert_eq!(graph.edge_count(), 2);
+
+        let mut multigraph = Graph::with_policy(GraphKind::Undirected, EdgePolicy::Multigraph);
+        let tank = multigraph.add_node("tank");
+        let first = multigraph.add_edge(tank, tank, ()).unwrap();
+        let second = multigraph.add_edge(tank, tank, ()).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(multigraph.degree(tank), 4);
+    }
+
+    // The same pseudo-random plant as HashMap lists, a Graph, interned IDs
+    // and CSR must give the same BFS reach (including a detached island)
+    #[test]
+    fn representations_reach_the_same_units() {
+        let n = 500;
+        let mut state = 0x2545_F491u32;
+        let mut random_unit = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as usize % n
+        };
+        let labels: Vec<String> = (0..n).map(|i| format!("Unit-{:03}", i)).collect();
+        let mut pairs = Vec::new();
+        for i in 0..n {
+            // Every tenth unit drops its chain link, splitting the plant
+            let chain = if i.is_multiple_of(10) { n } else { i + 1 };
+            for j in [chain, random_unit(), random_unit()] {
+                if j < n && j != i && random_unit().is_multiple_of(3) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+
+        let mut keyed: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut handled: Graph<String, ()> = Graph::with_policy(
+            GraphKind::Undirected,
+            EdgePolicy::Multigraph,
+        );
+        let handles: Vec<NodeIndex> =
+            labels.iter().map(|label| handled.add_node(label.clone())).collect();
+        let mut interned = InternedGraph::new(GraphKind::Undirected);
+        for label in &labels {
+            interned.intern(label);
+        }
+        for &(a, b) in &pairs {
+            keyed.entry(&labels[a]).or_default().push(&labels[b]);
+            keyed.entry(&labels[b]).or_default().push(&labels[a]);
+            handled.add_edge(handles[a], handles[b], ()).unwrap();
+            interned.add_edge(&labels[a], &labels[b]);
+        }
+        let csr = interned.to_csr();
+
+        for start in [0, n / 2, n - 1] {
+            let mut keyed_reach = HashSet::from([labels[start].as_str()]);
+            let mut queue = vec![labels[start].as_str()];
+            while let Some(node) = queue.pop() {
+                for &neighbor in keyed.get(node).into_iter().flatten() {
+                    if keyed_reach.insert(neighbor) {
+                        queue.push(neighbor);
+                    }
+                }
+            }
+
+            let handled_reach: HashSet<&str> = handled
+                .bfs_iter(handles[start])
+                .unwrap()
+                .map(|step| handled.node_weight(step.node).unwrap().as_str())
+                .collect();
+            let id = interned.id(&labels[start]).unwrap();
+            let interned_reach: HashSet<&str> =
+                interned.bfs(id).into_iter().map(|id| interned.label(id)).collect();
+            let csr_reach: HashSet<&str> =
+                csr.bfs(id).into_iter().map(|id| interned.label(id)).collect();
+
+            assert!(keyed_reach.len() < n, "the fixture should not be fully connected");
+            assert_eq!(handled_reach, keyed_reach);
+            assert_eq!(interned_reach, keyed_reach);
+            assert_eq!(csr_reach, keyed_reach);
+        }
+    }
 }
@@ -0,0 +1,299 @@
+// CONNECTIVITY (Components, Cut Points, Bridges, SCCs)
+// - connected_components: groups of nodes linked by some path, ignoring
+//   edge direction (weakly connected components for directed graphs)
+// - articulation_points / bridges: nodes and edges whose failure splits
+//   a component, i.e. single points of failure (Tarjan, one DFS pass)
+// - strongly_connected_components: groups where every node reaches every
+//   other along the edge directions (Kosaraju, two DFS passes)
+// Tarjan's low-link: low[v] is the earliest discovery time reachable from
+// v's DFS subtree using one non-tree edge; a child that cannot get above
+// its parent is cut off from the rest when the parent (or the tree edge
+// between them) goes away; only that exact tree edge is ignored when
+// looking for a way back, so a parallel copy of it counts as a backup
+// Every result is sorted by handle, i.e. in insertion order
+// Use cases: network resilience, redundancy planning, loop detection
+
+use std::collections::BTreeSet;
+
+use super::{EdgeIndex, Graph, NodeIndex};
+
+// Articulation points and bridges from one DFS
+struct CutStructure {
+    points: BTreeSet<NodeIndex>,
+    bridges: BTreeSet<EdgeIndex>,
+}
+
+// One level of the iterative Tarjan DFS
+struct Frame {
+    node: NodeIndex,
+    // The tree edge this node was reached by; None for a root
+    tree_edge: Option<EdgeIndex>,
+    links: Vec<(EdgeIndex, NodeIndex)>,
+    next: usize,
+    children: usize,
+}
+
+impl<N, E> Graph<N, E> {
+    // Edges with direction ignored, as (edge, far end): a directed edge is
+    // listed at its tail and its head, like an undirected one
+    fn links(&self, node: NodeIndex) -> Vec<(EdgeIndex, NodeIndex)> {
+        let mut links: Vec<(EdgeIndex, NodeIndex)> =
+            self.edges(node).map(|edge| (edge.id, edge.target)).collect();
+        if self.is_directed() {
+            links.extend(self.incoming_edges(node).map(|edge| (edge.id, edge.source)));
+        }
+        links
+    }
+
+    pub fn connected_components(&self) -> Vec<Vec<NodeIndex>> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut components = Vec::new();
+        for root in self.nodes() {
+            if seen[root.index()] {
+                continue;
+            }
+            seen[root.index()] = true;
+            let mut component = vec![root];
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                for (_, next) in self.links(node) {
+                    if !seen[next.index()] {
+                        seen[next.index()] = true;
+                        component.push(next);
+                        stack.push(next);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+
+    fn cut_structure(&self) -> CutStructure {
+        // Discovery time and low-link, indexed by handle
+        let mut discovered: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut low = vec![0; self.nodes.len()];
+        let mut time = 0;
+        let mut points = BTreeSet::new();
+        let mut bridges = BTreeSet::new();
+
+        for root in self.nodes() {
+            if discovered[root.index()].is_some() {
+                continue;
+            }
+            discovered[root.index()] = Some(time);
+            low[root.index()] = time;
+            time += 1;
+            let mut stack = vec![Frame {
+                node: root,
+                tree_edge: None,
+                links: self.links(root),
+                next: 0,
+                children: 0,
+            }];
+
+            while let Some(frame) = stack.last_mut() {
+                if let Some(&(edge, next)) = frame.links.get(frame.next) {
+                    frame.next += 1;
+                    // The tree edge back to the parent is not an escape route
+                    if Some(edge) == frame.tree_edge {
+                        continue;
+                    }
+                    match discovered[next.index()] {
+                        Some(seen_at) => {
+                            let node_low = &mut low[frame.node.index()];
+                            *node_low = (*node_low).min(seen_at);
+                        }
+                        None => {
+                            frame.children += 1;
+                            discovered[next.index()] = Some(time);
+                            low[next.index()] = time;
+                            time += 1;
+                            stack.push(Frame {
+                                node: next,
+                                tree_edge: Some(edge),
+                                links: self.links(next),
+                                next: 0,
+                                children: 0,
+                            });
+                        }
+                    }
+                    continue;
+                }
+
+                // Subtree finished: report what depends on the parent
+                let done = stack.pop().unwrap();
+                let Some(tree_edge) = done.tree_edge else {
+                    // The root is a cut point only if it has several subtrees
+                    if done.children > 1 {
+                        points.insert(done.node);
+                    }
+                    continue;
+                };
+                let parent = stack.last().unwrap();
+                let child_low = low[done.node.index()];
+                let parent_low = &mut low[parent.node.index()];
+                *parent_low = (*parent_low).min(child_low);
+                let parent_time = discovered[parent.node.index()].unwrap();
+                if child_low > parent_time {
+                    bridges.insert(tree_edge);
+                }
+                if child_low >= parent_time && parent.tree_edge.is_some() {
+                    points.insert(parent.node);
+                }
+            }
+        }
+        CutStructure { points, bridges }
+    }
+
+    // Nodes whose removal disconnects part of their component
+    pub fn articulation_points(&self) -> Vec<NodeIndex> {
+        self.cut_structure().points.into_iter().collect()
+    }
+
+    // Edges whose removal disconnects part of their component
+    pub fn bridges(&self) -> Vec<EdgeIndex> {
+        self.cut_structure().bridges.into_iter().collect()
+    }
+
+    // Kosaraju: finish order on the graph, then collect components on the
+    // reversed graph, starting from the last node to finish
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeIndex>> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut finish_order = Vec::with_capacity(self.node_count());
+        for root in self.nodes() {
+            if seen[root.index()] {
+                continue;
+            }
+            seen[root.index()] = true;
+            let mut stack = vec![(root, self.edges(root))];
+            while let Some((node, edges)) = stack.last_mut() {
+                match edges.next() {
+                    Some(edge) => {
+                        if !seen[edge.target.index()] {
+                            seen[edge.target.index()] = true;
+                            stack.push((edge.target, self.edges(edge.target)));
+                        }
+                    }
+                    None => {
+                        finish_order.push(*node);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        // Following predecessors walks the reversed graph without building it
+        let mut assigned = vec![false; self.nodes.len()];
+        let mut components = Vec::new();
+        for &root in finish_order.iter().rev() {
+            if assigned[root.index()] {
+                continue;
+            }
+            assigned[root.index()] = true;
+            let mut component = vec![root];
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                for next in self.predecessors(node) {
+                    if !assigned[next.index()] {
+                        assigned[next.index()] = true;
+                        component.push(next);
+                        stack.push(next);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        components.sort();
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{EdgePolicy, GraphKind};
+    use super::*;
+
+    // Nodes 0..units joined by the given links, with their handles
+    fn build(
+        kind: GraphKind,
+        policy: EdgePolicy,
+        units: usize,
+        links: &[(usize, usize)],
+    ) -> (Graph<usize, ()>, Vec<NodeIndex>, Vec<EdgeIndex>) {
+        let mut graph = Graph::with_policy(kind, policy);
+        let nodes: Vec<NodeIndex> = (0..units).map(|unit| graph.add_node(unit)).collect();
+        let edges = links
+            .iter()
+            .map(|&(from, to)| graph.add_edge(nodes[from], nodes[to], ()).unwrap())
+            .collect();
+        (graph, nodes, edges)
+    }
+
+    // tank - pump - exchanger, with exchanger, reactor and separator on
+    // a loop, then separator - valve - flare as a dead-end line:
+    //
+    //   0 - 1 - 2 - 3
+    //            \ /
+    //             4 - 5 - 6
+    #[test]
+    fn cut_points_and_bridges_on_a_plant() {
+        let links = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 2), (4, 5), (5, 6)];
+        let (graph, nodes, edges) =
+            build(GraphKind::Undirected, EdgePolicy::Simple, 7, &links);
+        assert_eq!(graph.articulation_points(), [nodes[1], nodes[2], nodes[4], nodes[5]]);
+        assert_eq!(graph.bridges(), [edges[0], edges[1], edges[5], edges[6]]);
+    }
+
+    #[test]
+    fn parallel_links_are_backups() {
+        let links = [(0, 1), (0, 1), (1, 2)];
+        let (graph, nodes, edges) =
+            build(GraphKind::Undirected, EdgePolicy::Multigraph, 3, &links);
+        assert_eq!(graph.articulation_points(), [nodes[1]]);
+        assert_eq!(graph.bridges(), [edges[2]]);
+    }
+
+    #[test]
+    fn isolated_nodes_are_their_own_component() {
+        let (graph, nodes, edges) =
+            build(GraphKind::Undirected, EdgePolicy::Simple, 4, &[(0, 2), (2, 3)]);
+        assert_eq!(
+            graph.connected_components(),
+            [vec![nodes[0], nodes[2], nodes[3]], vec![nodes[1]]]
+        );
+        assert_eq!(graph.articulation_points(), [nodes[2]]);
+        assert_eq!(graph.bridges(), edges);
+
+        let (lonely, nodes, _) = build(GraphKind::Undirected, EdgePolicy::Simple, 2, &[]);
+        assert_eq!(lonely.connected_components(), [vec![nodes[0]], vec![nodes[1]]]);
+        assert!(lonely.articulation_points().is_empty());
+        assert!(lonely.bridges().is_empty());
+    }
+
+    #[test]
+    fn directed_components_ignore_direction() {
+        // 0 → 1 ← 2 never reaches 2 from 0 along the arrows, and 3 → 4
+        let (graph, nodes, _) =
+            build(GraphKind::Directed, EdgePolicy::Simple, 5, &[(0, 1), (2, 1), (3, 4)]);
+        assert_eq!(
+            graph.connected_components(),
+            [vec![nodes[0], nodes[1], nodes[2]], vec![nodes[3], nodes[4]]]
+        );
+    }
+
+    #[test]
+    fn strongly_connected_components_split_at_one_way_links() {
+        // Two loops, 0 → 1 → 2 → 0 and 3 → 4 → 3, joined by 2 → 3, plus a
+        // unit 5 that only feeds the first loop
+        let links = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 3), (2, 3), (5, 0)];
+        let (graph, nodes, _) = build(GraphKind::Directed, EdgePolicy::Simple, 6, &links);
+        assert_eq!(
+            graph.strongly_connected_components(),
+            [vec![nodes[0], nodes[1], nodes[2]], vec![nodes[3], nodes[4]], vec![nodes[5]]]
+        );
+        assert_eq!(graph.connected_components().len(), 1);
+    }
+}
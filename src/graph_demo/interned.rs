@@ -0,0 +1,178 @@
+// INTERNED GRAPH (Integer Node IDs, CSR Adjacency)
+// Each label is stored once and mapped to a compact NodeId (a u32), so
+// adjacency lists hold 4-byte IDs instead of cloned Strings
+// - InternedGraph: Vec<Vec<NodeId>> adjacency, cheap to grow
+// - CsrGraph: compressed sparse row, built once from an InternedGraph;
+//   all edges sit in one array and node i's neighbors are
+//   targets[offsets[i]..offsets[i + 1]], so a walk touches contiguous memory
+// Labels only matter at the edges of the program: look an ID up once,
+// run the algorithm on IDs, turn the answer back into labels
+// Parallel edges are kept (a multigraph); callers dedupe if they need to
+// Use cases: 100k-node plant models, graph analytics, anything hot-looped
+
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use super::GraphKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug)]
+pub struct InternedGraph {
+    kind: GraphKind,
+    labels: Vec<String>,          // NodeId → label
+    ids: HashMap<String, NodeId>, // label → NodeId
+    adjacency: Vec<Vec<NodeId>>,  // Indexed by NodeId
+}
+
+// Frozen adjacency; shares the NodeIds of the graph it was built from
+#[derive(Debug)]
+pub struct CsrGraph {
+    offsets: Vec<usize>, // node_count + 1 entries
+    targets: Vec<NodeId>,
+}
+
+// Visits nodes level by level, tracking seen nodes in a Vec<bool>
+// indexed by ID instead of a HashSet of labels
+fn bfs_order<'a>(
+    node_count: usize,
+    start: NodeId,
+    neighbors: impl Fn(NodeId) -> &'a [NodeId],
+) -> Vec<NodeId> {
+    let mut seen = vec![false; node_count];
+    seen[start.index()] = true;
+    let mut order = vec![start];
+    let mut next = 0;
+    // `order` doubles as the queue: everything after `next` is waiting
+    while let Some(&node) = order.get(next) {
+        next += 1;
+        for &neighbor in neighbors(node) {
+            if !seen[neighbor.index()] {
+                seen[neighbor.index()] = true;
+                order.push(neighbor);
+            }
+        }
+    }
+    order
+}
+
+impl InternedGraph {
+    pub fn new(kind: GraphKind) -> Self {
+        InternedGraph {
+            kind,
+            labels: Vec::new(),
+            ids: HashMap::new(),
+            adjacency: Vec::new(),
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        let stored: usize = self.adjacency.iter().map(Vec::len).sum();
+        match self.kind {
+            GraphKind::Directed => stored,
+            GraphKind::Undirected => stored / 2,
+        }
+    }
+
+    // The ID for `label`, adding the node on first sight; the label is
+    // only cloned then
+    pub fn intern(&mut self, label: &str) -> NodeId {
+        if let Some(&id) = self.ids.get(label) {
+            return id;
+        }
+        let id = NodeId(u32::try_from(self.labels.len()).expect("more than u32::MAX nodes"));
+        self.labels.push(label.to_string());
+        self.ids.insert(label.to_string(), id);
+        self.adjacency.push(Vec::new());
+        id
+    }
+
+    pub fn id(&self, label: &str) -> Option<NodeId> {
+        self.ids.get(label).copied()
+    }
+
+    pub fn label(&self, id: NodeId) -> &str {
+        &self.labels[id.index()]
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        let from = self.intern(from);
+        let to = self.intern(to);
+        self.add_edge_ids(from, to);
+    }
+
+    // Hot path once the IDs are known: no hashing at all
+    pub fn add_edge_ids(&mut self, from: NodeId, to: NodeId) {
+        self.adjacency[from.index()].push(to);
+        if self.kind == GraphKind::Undirected {
+            self.adjacency[to.index()].push(from);
+        }
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> &[NodeId] {
+        &self.adjacency[id.index()]
+    }
+
+    pub fn bfs(&self, start: NodeId) -> Vec<NodeId> {
+        bfs_order(self.node_count(), start, |id| self.neighbors(id))
+    }
+
+    // Packs the adjacency lists into two flat arrays
+    pub fn to_csr(&self) -> CsrGraph {
+        let mut offsets = Vec::with_capacity(self.adjacency.len() + 1);
+        let mut targets = Vec::with_capacity(self.adjacency.iter().map(Vec::len).sum());
+        offsets.push(0);
+        for neighbors in &self.adjacency {
+            targets.extend_from_slice(neighbors);
+            offsets.push(targets.len());
+        }
+        CsrGraph { offsets, targets }
+    }
+
+    // Approximate heap bytes: labels (twice, once per map direction), the
+    // hash map's slots and the adjacency vectors
+    pub fn heap_bytes(&self) -> usize {
+        let labels: usize = self.labels.iter().map(String::capacity).sum();
+        let map_slots = self.ids.capacity() * size_of::<(String, NodeId)>();
+        let adjacency: usize = self
+            .adjacency
+            .iter()
+            .map(|neighbors| neighbors.capacity() * size_of::<NodeId>())
+            .sum();
+        2 * labels
+            + self.labels.capacity() * size_of::<String>()
+            + map_slots
+            + self.adjacency.capacity() * size_of::<Vec<NodeId>>()
+            + adjacency
+    }
+}
+
+impl CsrGraph {
+    pub fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> &[NodeId] {
+        &self.targets[self.offsets[id.index()]..self.offsets[id.index() + 1]]
+    }
+
+    pub fn bfs(&self, start: NodeId) -> Vec<NodeId> {
+        bfs_order(self.node_count(), start, |id| self.neighbors(id))
+    }
+
+    // Adjacency only; labels stay with the InternedGraph
+    pub fn heap_bytes(&self) -> usize {
+        self.offsets.capacity() * size_of::<usize>() + self.targets.capacity() * size_of::<NodeId>()
+    }
+}
@@ -0,0 +1,243 @@
+// TOPOLOGICAL SORT AND CYCLE DETECTION
+// A topological order lists every node before all of its successors,
+// e.g. a startup order where each unit starts after everything it needs
+// One exists exactly when the directed graph has no cycle, so both
+// variants return the offending cycle instead when there is one
+// - Kahn's algorithm: repeatedly take a node with no remaining incoming
+//   edges; nodes that never get there are stuck behind a cycle
+// - DFS: a node is finished after all of its successors; reversed finish
+//   order is a topological order, and an edge back into the current path
+//   is a cycle
+// Cycles come back closed, first node repeated: [A, B, C, A]
+// Undirected graphs have no "before", so sorting one is an error too
+// Use cases: startup/shutdown sequencing, build systems, task scheduling
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{EdgeIndex, Graph, GraphKind, NodeIndex};
+
+// Why a graph has no topological order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    Undirected,
+    Cycle(Vec<NodeIndex>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    OnPath,
+    Finished,
+}
+
+impl<N, E> Graph<N, E> {
+    // Kahn's algorithm; ready nodes are taken in handle order so the
+    // result is reproducible
+    pub fn topological_sort(&self) -> Result<Vec<NodeIndex>, OrderError> {
+        if !self.is_directed() {
+            return Err(OrderError::Undirected);
+        }
+        // Indexed by handle; removed nodes stay at 0 and are never ready
+        let mut in_degree = vec![0; self.nodes.len()];
+        for node in self.nodes() {
+            in_degree[node.index()] = self.in_degree(node);
+        }
+        let mut ready: BTreeSet<NodeIndex> = self
+            .nodes()
+            .filter(|node| in_degree[node.index()] == 0)
+            .collect();
+
+        let mut order = Vec::with_capacity(self.node_count());
+        while let Some(node) = ready.pop_first() {
+            order.push(node);
+            for edge in self.edges(node) {
+                let degree = &mut in_degree[edge.target.index()];
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.insert(edge.target);
+                }
+            }
+        }
+        if order.len() == self.node_count() {
+            return Ok(order);
+        }
+
+        // Every leftover node still has an edge in from another leftover
+        // node, so walking those edges backwards must revisit a node
+        let stuck = |node: NodeIndex| in_degree[node.index()] > 0;
+        let mut node = self.nodes().find(|&node| stuck(node)).unwrap();
+        let mut walk: Vec<NodeIndex> = Vec::new();
+        let mut position: HashMap<NodeIndex, usize> = HashMap::new();
+        while !position.contains_key(&node) {
+            position.insert(node, walk.len());
+            walk.push(node);
+            node = self
+                .incoming_edges(node)
+                .map(|edge| edge.source)
+                .find(|&from| stuck(from))
+                .unwrap();
+        }
+        // The walk runs against the edges; turn the loop around
+        let mut cycle = vec![node];
+        cycle.extend(walk[position[&node] + 1..].iter().rev());
+        cycle.push(node);
+        Err(OrderError::Cycle(cycle))
+    }
+
+    // Depth-first variant with an explicit stack of (node, edges still to
+    // follow) pairs; the stack is always the path from the current root
+    pub fn topological_sort_dfs(&self) -> Result<Vec<NodeIndex>, OrderError> {
+        if !self.is_directed() {
+            return Err(OrderError::Undirected);
+        }
+        let mut marks: Vec<Option<Mark>> = vec![None; self.nodes.len()];
+        let mut finished = Vec::with_capacity(self.node_count());
+
+        for root in self.nodes() {
+            if marks[root.index()].is_some() {
+                continue;
+            }
+            marks[root.index()] = Some(Mark::OnPath);
+            let mut stack = vec![(root, self.edges(root))];
+            while let Some((node, edges)) = stack.last_mut() {
+                let Some(edge) = edges.next() else {
+                    // All successors are done, so this node is too
+                    marks[node.index()] = Some(Mark::Finished);
+                    finished.push(*node);
+                    stack.pop();
+                    continue;
+                };
+                let to = edge.target;
+                match marks[to.index()] {
+                    None => {
+                        marks[to.index()] = Some(Mark::OnPath);
+                        stack.push((to, self.edges(to)));
+                    }
+                    Some(Mark::OnPath) => {
+                        // Back edge: the path from `to` down to here is a loop
+                        let start = stack.iter().position(|(node, _)| *node == to).unwrap();
+                        let mut cycle: Vec<NodeIndex> =
+                            stack[start..].iter().map(|(node, _)| *node).collect();
+                        cycle.push(to);
+                        return Err(OrderError::Cycle(cycle));
+                    }
+                    Some(Mark::Finished) => {}
+                }
+            }
+        }
+        finished.reverse();
+        Ok(finished)
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        match self.kind {
+            GraphKind::Directed => self.topological_sort().is_err(),
+            // Each undirected edge is listed at both ends, so the edge a
+            // node was reached by is skipped on the way out; it is matched
+            // by handle, so a parallel copy still counts. Seeing any other
+            // visited node means two different routes lead to it
+            GraphKind::Undirected => {
+                let mut visited = vec![false; self.nodes.len()];
+                for root in self.nodes() {
+                    if visited[root.index()] {
+                        continue;
+                    }
+                    visited[root.index()] = true;
+                    let mut stack: Vec<(NodeIndex, Option<EdgeIndex>)> = vec![(root, None)];
+                    while let Some((node, reached_by)) = stack.pop() {
+                        for edge in self.edges(node) {
+                            if Some(edge.id) == reached_by {
+                                continue;
+                            }
+                            if visited[edge.target.index()] {
+                                return true;
+                            }
+                            visited[edge.target.index()] = true;
+                            stack.push((edge.target, Some(edge.id)));
+                        }
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::EdgePolicy;
+    use super::*;
+
+    // Every edge must point forwards in the order
+    fn respects_edges<N, E>(graph: &Graph<N, E>, order: &[NodeIndex]) -> bool {
+        let position = |node| order.iter().position(|&other| other == node);
+        order.len() == graph.node_count()
+            && graph.nodes().all(|from| {
+                graph.successors(from).into_iter().all(|to| position(from) < position(to))
+            })
+    }
+
+    // Consecutive cycle nodes must be joined by edges, and the cycle closed
+    fn is_cycle<N, E>(graph: &Graph<N, E>, cycle: &[NodeIndex]) -> bool {
+        cycle.len() >= 2
+            && cycle.first() == cycle.last()
+            && cycle.windows(2).all(|pair| graph.has_edge(pair[0], pair[1]))
+    }
+
+    // pump → exchanger → reactor → separator, pump → reactor, valve alone
+    fn startup() -> (Graph<&'static str, ()>, [NodeIndex; 5]) {
+        let mut graph = Graph::with_kind(GraphKind::Directed);
+        let units = ["separator", "reactor", "exchanger", "pump", "valve"];
+        let [separator, reactor, exchanger, pump, valve] = units.map(|unit| graph.add_node(unit));
+        for (from, to) in [
+            (pump, exchanger),
+            (exchanger, reactor),
+            (reactor, separator),
+            (pump, reactor),
+        ] {
+            graph.add_edge(from, to, ()).unwrap();
+        }
+        (graph, [separator, reactor, exchanger, pump, valve])
+    }
+
+    #[test]
+    fn acyclic_graphs_sort_both_ways() {
+        let (graph, [.., valve]) = startup();
+        let kahn = graph.topological_sort().unwrap();
+        assert!(respects_edges(&graph, &kahn), "{:?}", kahn);
+        assert!(kahn.contains(&valve));
+        let dfs = graph.topological_sort_dfs().unwrap();
+        assert!(respects_edges(&graph, &dfs), "{:?}", dfs);
+        assert!(!graph.has_cycle());
+    }
+
+    #[test]
+    fn cycles_are_reported_closed() {
+        let (mut graph, [separator, _, _, pump, _]) = startup();
+        graph.add_edge(separator, pump, ()).unwrap();
+        for result in [graph.topological_sort(), graph.topological_sort_dfs()] {
+            match result {
+                Err(OrderError::Cycle(cycle)) => assert!(is_cycle(&graph, &cycle), "{:?}", cycle),
+                other => panic!("expected a cycle, got {:?}", other),
+            }
+        }
+        assert!(graph.has_cycle());
+
+        let mut looped: Graph<&str, ()> =
+            Graph::with_policy(GraphKind::Directed, EdgePolicy::Multigraph);
+        let tank = looped.add_node("tank");
+        looped.add_edge(tank, tank, ()).unwrap();
+        assert_eq!(looped.topological_sort(), Err(OrderError::Cycle(vec![tank, tank])));
+        assert_eq!(looped.topological_sort_dfs(), Err(OrderError::Cycle(vec![tank, tank])));
+    }
+
+    #[test]
+    fn undirected_graphs_have_no_order() {
+        let mut graph = Graph::with_kind(GraphKind::Undirected);
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.add_edge(a, b, ()).unwrap();
+        assert_eq!(graph.topological_sort(), Err(OrderError::Undirected));
+        assert_eq!(graph.topological_sort_dfs(), Err(OrderError::Undirected));
+        assert!(!graph.has_cycle());
+    }
+}
@@ -0,0 +1,363 @@
+// GRAPH TRAVERSAL (Lazy BFS / DFS and Visitors)
+// bfs_iter / dfs_iter walk the graph one node per next() call, yielding
+// each node once with its depth (edges from the start) and the node it
+// was reached from, so callers can stop early or collect what they need
+// - BFS visits level by level: depths never decrease, and the parent
+//   links form a shortest-hop tree
+// - DFS follows one branch as deep as it goes before backing up
+// Visitor hooks (discover / examine_edge / finish) expose the full DFS
+// event sequence for custom algorithms
+// Edges are followed in their direction; handles of removed nodes are errors
+// Use cases: reachability, hop counts, dependency walks, audits
+
+use std::collections::{HashMap, VecDeque};
+
+use super::{EdgeRef, Graph, NodeIndex};
+
+// One node as a traversal reaches it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub node: NodeIndex,
+    pub depth: usize,
+    pub parent: Option<NodeIndex>,
+}
+
+// Callbacks for depth_first_visit over a graph with edge payload E;
+// every hook defaults to doing nothing
+pub trait Visitor<E> {
+    // First time the search reaches a node
+    fn discover(&mut self, _step: &Step) {}
+    // Every edge leaving a discovered node, including edges to nodes
+    // that were already discovered
+    fn examine_edge(&mut self, _edge: &EdgeRef<E>) {}
+    // All edges of the node are done, as is everything discovered below it
+    fn finish(&mut self, _node: NodeIndex) {}
+}
+
+pub struct Bfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    queue: VecDeque<Step>,
+    seen: Vec<bool>, // Indexed by node handle
+}
+
+impl<N, E> Iterator for Bfs<'_, N, E> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let step = self.queue.pop_front()?;
+        for edge in self.graph.edges(step.node) {
+            if !self.seen[edge.target.index()] {
+                self.seen[edge.target.index()] = true;
+                self.queue.push_back(Step {
+                    node: edge.target,
+                    depth: step.depth + 1,
+                    parent: Some(step.node),
+                });
+            }
+        }
+        Some(step)
+    }
+}
+
+// Nodes are marked when popped rather than when pushed, so a node is
+// reached through the deepest branch that gets to it first
+pub struct Dfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    stack: Vec<Step>,
+    seen: Vec<bool>,
+}
+
+impl<N, E> Iterator for Dfs<'_, N, E> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let step = self.stack.pop()?;
+            if self.seen[step.node.index()] {
+                continue; // Pushed twice; the earlier copy won
+            }
+            self.seen[step.node.index()] = true;
+            // Reversed so the first edge is explored first
+            for edge in self.graph.edges(step.node).rev() {
+                if !self.seen[edge.target.index()] {
+                    self.stack.push(Step {
+                        node: edge.target,
+                        depth: step.depth + 1,
+                        parent: Some(step.node),
+                    });
+                }
+            }
+            return Some(step);
+        }
+    }
+}
+
+impl<N, E> Graph<N, E> {
+    fn first_step(&self, start: NodeIndex) -> Result<Step, String> {
+        Ok(Step {
+            node: self.check_node(start)?,
+            depth: 0,
+            parent: None,
+        })
+    }
+
+    pub fn bfs_iter(&self, start: NodeIndex) -> Result<Bfs<'_, N, E>, String> {
+        let first = self.first_step(start)?;
+        let mut seen = vec![false; self.nodes.len()];
+        seen[start.index()] = true;
+        Ok(Bfs {
+            graph: self,
+            queue: VecDeque::from([first]),
+            seen,
+        })
+    }
+
+    pub fn dfs_iter(&self, start: NodeIndex) -> Result<Dfs<'_, N, E>, String> {
+        Ok(Dfs {
+            graph: self,
+            stack: vec![self.first_step(start)?],
+            seen: vec![false; self.nodes.len()],
+        })
+    }
+
+    // Every node reachable from `start` (including itself), in handle order
+    pub fn reachable_from(&self, start: NodeIndex) -> Result<Vec<NodeIndex>, String> {
+        let mut nodes: Vec<NodeIndex> = self.bfs_iter(start)?.map(|step| step.node).collect();
+        nodes.sort();
+        Ok(nodes)
+    }
+
+    // Fewest edges from `from` to `to`, ignoring payloads; Ok(None) when
+    // `to` cannot be reached
+    pub fn shortest_hop_path(
+        &self,
+        from: NodeIndex,
+        to: NodeIndex,
+    ) -> Result<Option<Vec<NodeIndex>>, String> {
+        let to = self.check_node(to)?;
+        let mut parents: HashMap<NodeIndex, Option<NodeIndex>> = HashMap::new();
+        for step in self.bfs_iter(from)? {
+            parents.insert(step.node, step.parent);
+            if step.node == to {
+                // BFS reaches every node first along a shortest path
+                let mut path = vec![to];
+                while let Some(&Some(parent)) = parents.get(path.last().unwrap()) {
+                    path.push(parent);
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+
+    // Depth-first search from `start` reporting every event to `visitor`
+    pub fn depth_first_visit(
+        &self,
+        start: NodeIndex,
+        visitor: &mut impl Visitor<E>,
+    ) -> Result<(), String> {
+        let first = self.first_step(start)?;
+        visitor.discover(&first);
+        let mut seen = vec![false; self.nodes.len()];
+        seen[start.index()] = true;
+        // (step, its edges still to examine); the stack is the path from
+        // the start to the current node
+        let mut stack = vec![(first, self.edges(start))];
+        while let Some((step, edges)) = stack.last_mut() {
+            let Some(edge) = edges.next() else {
+                visitor.finish(step.node);
+                stack.pop();
+                continue;
+            };
+            visitor.examine_edge(&edge);
+            if !seen[edge.target.index()] {
+                seen[edge.target.index()] = true;
+                let child = Step {
+                    node: edge.target,
+                    depth: step.depth + 1,
+                    parent: Some(step.node),
+                };
+                visitor.discover(&child);
+                stack.push((child, self.edges(child.node)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::GraphKind;
+    use super::*;
+
+    //   +-------------+
+    //   v             |
+    //   a ---> b ---> d
+    //   |      ^
+    //   v      |
+    //   c -----+          e (unreachable)
+    fn plant() -> (Graph<&'static str, ()>, [NodeIndex; 5]) {
+        let mut graph = Graph::with_kind(GraphKind::Directed);
+        let [a, b, c, d, e] = ["a", "b", "c", "d", "e"].map(|name| graph.add_node(name));
+        for (from, to) in [(a, b), (a, c), (b, d), (c, b), (d, a)] {
+            graph.add_edge(from, to, ()).unwrap();
+        }
+        (graph, [a, b, c, d, e])
+    }
+
+    fn step(node: NodeIndex, depth: usize, parent: Option<NodeIndex>) -> Step {
+        Step {
+            node,
+            depth,
+            parent,
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Discover(NodeIndex),
+        TreeEdge(NodeIndex, NodeIndex),
+        // To a node still on the search path: the graph has a cycle
+        BackEdge(NodeIndex, NodeIndex),
+        // To a node that is already finished
+        ForwardOrCross(NodeIndex, NodeIndex),
+        Finish(NodeIndex),
+    }
+
+    // Classifies each examined edge by the state of its target, which is
+    // only known once the next event arrives
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<Event>,
+        on_path: Vec<NodeIndex>,
+        finished: Vec<NodeIndex>,
+        pending: Option<(NodeIndex, NodeIndex)>,
+    }
+
+    impl Recorder {
+        fn settle(&mut self, discovered: Option<NodeIndex>) {
+            let Some((from, to)) = self.pending.take() else {
+                return;
+            };
+            self.events.push(if discovered == Some(to) {
+                Event::TreeEdge(from, to)
+            } else if self.on_path.contains(&to) {
+                Event::BackEdge(from, to)
+            } else {
+                assert!(self.finished.contains(&to), "edge to an unseen node skipped");
+                Event::ForwardOrCross(from, to)
+            });
+        }
+    }
+
+    impl<E> Visitor<E> for Recorder {
+        fn discover(&mut self, step: &Step) {
+            self.settle(Some(step.node));
+            self.events.push(Event::Discover(step.node));
+            self.on_path.push(step.node);
+        }
+
+        fn examine_edge(&mut self, edge: &EdgeRef<E>) {
+            self.settle(None);
+            self.pending = Some((edge.source, edge.target));
+        }
+
+        fn finish(&mut self, node: NodeIndex) {
+            self.settle(None);
+            assert_eq!(self.on_path.pop(), Some(node));
+            self.finished.push(node);
+            self.events.push(Event::Finish(node));
+        }
+    }
+
+    #[test]
+    fn bfs_goes_level_by_level() {
+        let (graph, [a, b, c, d, e]) = plant();
+        let steps: Vec<Step> = graph.bfs_iter(a).unwrap().collect();
+        let expected = [
+            step(a, 0, None),
+            step(b, 1, Some(a)),
+            step(c, 1, Some(a)),
+            step(d, 2, Some(b)),
+        ];
+        assert_eq!(steps, expected);
+        let from_d: Vec<Step> = graph.bfs_iter(d).unwrap().take(2).collect();
+        assert_eq!(from_d, [step(d, 0, None), step(a, 1, Some(d))]);
+        assert_eq!(graph.reachable_from(c), Ok(vec![a, b, c, d]));
+        assert_eq!(graph.reachable_from(e), Ok(vec![e]));
+    }
+
+    #[test]
+    fn dfs_follows_one_branch_first() {
+        let (graph, [a, b, c, d, _]) = plant();
+        let steps: Vec<Step> = graph.dfs_iter(a).unwrap().collect();
+        let expected = [
+            step(a, 0, None),
+            step(b, 1, Some(a)),
+            step(d, 2, Some(b)),
+            step(c, 1, Some(a)),
+        ];
+        assert_eq!(steps, expected);
+
+        // x → z is listed first, but y's branch reaches z before the
+        // search backs up to it
+        let mut diamond = Graph::with_kind(GraphKind::Directed);
+        let [x, y, z] = ["x", "y", "z"].map(|name| diamond.add_node(name));
+        for (from, to) in [(x, y), (x, z), (y, z)] {
+            diamond.add_edge(from, to, ()).unwrap();
+        }
+        let steps: Vec<Step> = diamond.dfs_iter(x).unwrap().collect();
+        assert_eq!(steps, [step(x, 0, None), step(y, 1, Some(x)), step(z, 2, Some(y))]);
+    }
+
+    #[test]
+    fn visitor_sees_every_event_in_order() {
+        let (graph, [a, b, c, d, _]) = plant();
+        let mut recorder = Recorder::default();
+        graph.depth_first_visit(a, &mut recorder).unwrap();
+        let expected = [
+            Event::Discover(a),
+            Event::TreeEdge(a, b),
+            Event::Discover(b),
+            Event::TreeEdge(b, d),
+            Event::Discover(d),
+            Event::BackEdge(d, a),
+            Event::Finish(d),
+            Event::Finish(b),
+            Event::TreeEdge(a, c),
+            Event::Discover(c),
+            Event::ForwardOrCross(c, b),
+            Event::Finish(c),
+            Event::Finish(a),
+        ];
+        assert_eq!(recorder.events, expected);
+        assert!(recorder.on_path.is_empty());
+    }
+
+    #[test]
+    fn shortest_hop_paths() {
+        let (graph, [a, b, c, d, e]) = plant();
+        assert_eq!(graph.shortest_hop_path(a, d), Ok(Some(vec![a, b, d])));
+        assert_eq!(graph.shortest_hop_path(d, c), Ok(Some(vec![d, a, c])));
+        assert_eq!(graph.shortest_hop_path(c, c), Ok(Some(vec![c])));
+        assert_eq!(graph.shortest_hop_path(a, e), Ok(None));
+        assert_eq!(graph.shortest_hop_path(e, a), Ok(None));
+    }
+
+    #[test]
+    fn unknown_starts_are_errors() {
+        let (mut graph, [a, b, ..]) = plant();
+        graph.remove_node(b);
+        let stale = Err("no node #1 in the graph".to_string());
+        assert_eq!(graph.bfs_iter(b).map(|_| ()), stale);
+        assert_eq!(graph.dfs_iter(b).map(|_| ()), stale);
+        assert_eq!(graph.reachable_from(b).map(|_| ()), stale);
+        assert_eq!(graph.depth_first_visit(b, &mut Recorder::default()), stale);
+        assert_eq!(graph.shortest_hop_path(b, a).map(|_| ()), stale);
+        assert_eq!(graph.shortest_hop_path(a, b).map(|_| ()), stale);
+        let unknown = NodeIndex(99);
+        assert!(graph.bfs_iter(unknown).is_err());
+        assert!(graph.dfs_iter(unknown).is_err());
+    }
+}
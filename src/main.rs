@@ -1,4 +1,4 @@
-// Main application to demonstrate 8 core data structures
+// Main application to demonstrate 9 core data structures
 // Each data structure is implemented in its own module for clarity
 
 mod array_demo;
@@ -9,11 +9,12 @@ mod hash_table_demo;
 mod tree_demo;
 mod graph_demo;
 mod heap_demo;
+mod trie_demo;
 
 fn main() {
     println!("=================================================");
     println!("   RUST DATA STRUCTURES SHOWCASE");
-    println!("   Demonstrating 8 Core Data Structures");
+    println!("   Demonstrating 9 Core Data Structures");
     println!("=================================================\n");
 
     // 1. Array Demo
@@ -64,6 +65,12 @@ fn main() {
     println!("{:=<50}", "");
     heap_demo::demonstrate();
 
+    // 9. Trie Demo
+    println!("\n{:=<50}", "");
+    println!("9. TRIE - Prefix Tree for String Keys");
+    println!("{:=<50}", "");
+    trie_demo::demonstrate();
+
     println!("\n{:=<50}", "");
     println!("All demonstrations completed!");
     println!("{:=<50}\n", "");
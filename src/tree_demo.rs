@@ -2,121 +2,1444 @@
 // Hierarchical structure with nodes
 // Each node has at most 2 children (left and right)
 // BST property: left < parent < right
-// Operations: insert, search, traverse
+// Operations: insert, search, delete, traverse
 // Use cases: sorted data, hierarchical data, databases
 
+mod algorithms;
+mod avl;
+mod btree;
+mod codec;
+mod interval;
+mod iter;
+mod ordered;
+mod persistent;
+mod red_black;
+mod render;
+mod tree_map;
+
 use std::cmp::Ordering;
+use std::fmt::Display;
+use std::iter::once;
+use std::ops::Bound;
+
+use algorithms::{
+    contains_subtree, diameter, height, is_balanced, is_valid_bst, lowest_common_ancestor,
+    mirrored, nodes_at_level, path_sums, root_to_leaf_paths, same_tree,
+};
+use avl::AvlTree;
+use btree::BTree;
+use interval::{Interval, IntervalTree};
+// Re-exported for benches/tree_inserts.rs, which compiles this module on its own
+pub(crate) use red_black::RedBlackTree;
+use render::{Render, RenderNode};
+use persistent::{diff, PersistentTree};
+use iter::{drop_subtree, IntoIter, Iter, NodeValues};
+use ordered::{OrderedNode, OrderedTree};
+use tree_map::{Entry, TreeMap};
+
+// A possibly-empty subtree; the root of a tree is also a Link so that
+// deleting the root node can leave the tree empty
+pub type Link<T> = Option<Box<TreeNode<T>>>;
+
+// What a tree does with a value equal to one it already holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    // Set semantics: equal values are dropped
+    Reject,
+    // Multiset semantics: every copy is kept, in insertion order
+    Keep,
+}
+
+// Outcome of inserting into a subtree
+#[derive(Debug, PartialEq, Eq)]
+enum Insertion {
+    NewKey,
+    Duplicate,
+    Rejected,
+}
 
 #[derive(Debug)]
 pub struct TreeNode<T> {
     value: T,
-    left: Option<Box<TreeNode<T>>>,
-    right: Option<Box<TreeNode<T>>>,
+    // Later values equal to `value`, in insertion order (DuplicatePolicy::Keep)
+    duplicates: Vec<T>,
+    // Number of values in the subtree rooted here, duplicates included
+    // (order-statistic augmentation)
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<T> OrderedNode for TreeNode<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+
+    fn values(&self) -> NodeValues<'_, T> {
+        once(&self.value).chain(self.duplicates.iter())
+    }
+
+    fn count(&self) -> usize {
+        1 + self.duplicates.len()
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn take_left(&mut self) -> Link<T> {
+        self.left.take()
+    }
+
+    fn take_right(&mut self) -> Link<T> {
+        self.right.take()
+    }
+
+    fn into_values(self) -> (T, Vec<T>) {
+        (self.value, self.duplicates)
+    }
 }
 
 impl<T: Ord> TreeNode<T> {
     fn new(value: T) -> Self {
         TreeNode {
             value,
+            duplicates: Vec::new(),
+            size: 1,
             left: None,
             right: None,
         }
     }
 
-    fn insert(&mut self, value: T) {
-        match value.cmp(&self.value) {
+    fn insert(&mut self, value: T, policy: DuplicatePolicy) -> Insertion {
+        let outcome = match value.cmp(&self.value) {
             Ordering::Less => {
                 match &mut self.left {
-                    Some(left_node) => left_node.insert(value),
-                    None => self.left = Some(Box::new(TreeNode::new(value))),
+                    Some(left_node) => left_node.insert(value, policy),
+                    None => {
+                        self.left = Some(Box::new(TreeNode::new(value)));
+                        Insertion::NewKey
+                    }
                 }
             }
             Ordering::Greater => {
                 match &mut self.right {
-                    Some(right_node) => right_node.insert(value),
-                    None => self.right = Some(Box::new(TreeNode::new(value))),
+                    Some(right_node) => right_node.insert(value, policy),
+                    None => {
+                        self.right = Some(Box::new(TreeNode::new(value)));
+                        Insertion::NewKey
+                    }
                 }
             }
-            Ordering::Equal => {} // Value already exists
+            // Value already exists
+            Ordering::Equal => match policy {
+                DuplicatePolicy::Reject => Insertion::Rejected,
+                DuplicatePolicy::Keep => {
+                    self.duplicates.push(value);
+                    Insertion::Duplicate
+                }
+            },
+        };
+        if outcome != Insertion::Rejected {
+            self.size += 1;
         }
+        outcome
     }
 
-    fn search(&self, value: &T) -> bool {
+    fn search(&self, value: &T) -> Option<&TreeNode<T>> {
         match value.cmp(&self.value) {
-            Ordering::Equal => true,
+            Ordering::Equal => Some(self),
             Ordering::Less => {
                 match &self.left {
                     Some(left_node) => left_node.search(value),
-                    None => false,
+                    None => None,
                 }
             }
             Ordering::Greater => {
                 match &self.right {
                     Some(right_node) => right_node.search(value),
-                    None => false,
+                    None => None,
                 }
             }
         }
     }
 
-    fn in_order_traversal<'a>(&'a self, result: &mut Vec<&'a T>) {
-        if let Some(left) = &self.left {
-            left.in_order_traversal(result);
+    // Removes the most recently inserted copy of `value` from the subtree
+    // rooted at `link`, which may be the root
+    fn remove_one(link: &mut Link<T>, value: &T) -> Option<T> {
+        let node = link.as_mut()?;
+        let removed = match value.cmp(&node.value) {
+            Ordering::Less => TreeNode::remove_one(&mut node.left, value),
+            Ordering::Greater => TreeNode::remove_one(&mut node.right, value),
+            Ordering::Equal => match node.duplicates.pop() {
+                Some(copy) => Some(copy),
+                // Last copy: the node itself goes, so there is no size to fix here
+                None => return Some(TreeNode::unlink(link).value),
+            },
+        };
+        if removed.is_some() {
+            if let Some(node) = link {
+                node.size -= 1;
+            }
+        }
+        removed
+    }
+
+    // Detaches the node holding `value`, with all of its copies
+    fn remove_all(link: &mut Link<T>, value: &T) -> Option<Box<TreeNode<T>>> {
+        let node = link.as_mut()?;
+        let removed = match value.cmp(&node.value) {
+            Ordering::Less => TreeNode::remove_all(&mut node.left, value),
+            Ordering::Greater => TreeNode::remove_all(&mut node.right, value),
+            Ordering::Equal => return Some(TreeNode::unlink(link)),
+        };
+        if let (Some(node), Some(removed)) = (link.as_mut(), &removed) {
+            node.size -= removed.count();
+        }
+        removed
+    }
+
+    // Takes the node at a non-empty link out of the tree
+    fn unlink(link: &mut Link<T>) -> Box<TreeNode<T>> {
+        let mut node = link.take().expect("unlink needs a non-empty link");
+        *link = match (node.left.take(), node.right.take()) {
+            // Leaf: simply unlink it
+            (None, None) => None,
+            // One child: the child moves up into this position
+            (Some(child), None) | (None, Some(child)) => Some(child),
+            // Two children: the in-order successor (smallest value in
+            // the right subtree) replaces the removed node
+            (Some(left), Some(right)) => {
+                let mut right = Some(right);
+                let mut successor = TreeNode::take_min(&mut right);
+                successor.size = left.size + size(&right) + successor.count();
+                successor.left = Some(left);
+                successor.right = right;
+                Some(successor)
+            }
+        };
+        node
+    }
+
+    // Detaches the node holding the smallest value of a non-empty subtree
+    fn take_min(link: &mut Link<T>) -> Box<TreeNode<T>> {
+        match link {
+            Some(node) if node.left.is_some() => {
+                let min = TreeNode::take_min(&mut node.left);
+                node.size -= min.count();
+                min
+            }
+            _ => {
+                let mut min = link.take().expect("take_min needs a non-empty subtree");
+                *link = min.right.take();
+                min
+            }
+        }
+    }
+}
+
+// Owning wrapper around an optional root, so a tree can start out empty
+#[derive(Debug)]
+pub struct BinarySearchTree<T> {
+    root: Link<T>,
+    len: usize,
+    policy: DuplicatePolicy,
+}
+
+impl<T: Ord> BinarySearchTree<T> {
+    pub fn new() -> Self {
+        BinarySearchTree::with_policy(DuplicatePolicy::Reject)
+    }
+
+    pub fn with_policy(policy: DuplicatePolicy) -> Self {
+        BinarySearchTree {
+            root: None,
+            len: 0,
+            policy,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // The root link, for the helpers in `algorithms`
+    pub fn root(&self) -> &Link<T> {
+        &self.root
+    }
+
+    pub fn clear(&mut self) {
+        drop_subtree(self.root.take());
+        self.len = 0;
+    }
+
+    fn find(&self, value: &T) -> Option<&TreeNode<T>> {
+        self.root.as_ref()?.search(value)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.find(value).is_some()
+    }
+
+    // How many copies of `value` the tree holds (at most 1 under Reject)
+    pub fn count(&self, value: &T) -> usize {
+        self.find(value).map_or(0, |node| node.count())
+    }
+
+    // Returns true if the value was not already in the tree. Under
+    // DuplicatePolicy::Keep an equal value is still stored as another copy
+    pub fn insert(&mut self, value: T) -> bool {
+        let outcome = match &mut self.root {
+            Some(root) => root.insert(value, self.policy),
+            None => {
+                self.root = Some(Box::new(TreeNode::new(value)));
+                Insertion::NewKey
+            }
+        };
+        if outcome != Insertion::Rejected {
+            self.len += 1;
+        }
+        outcome == Insertion::NewKey
+    }
+
+    // Removes one copy of `value` (the most recently inserted one)
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        self.remove_one(value)
+    }
+
+    pub fn remove_one(&mut self, value: &T) -> Option<T> {
+        let removed = TreeNode::remove_one(&mut self.root, value);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    // Removes every copy of `value`, returned in insertion order
+    pub fn remove_all(&mut self, value: &T) -> Vec<T> {
+        match TreeNode::remove_all(&mut self.root, value) {
+            Some(node) => {
+                let node = *node;
+                self.len -= node.count();
+                once(node.value).chain(node.duplicates).collect()
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+impl<T: Ord> OrderedTree for BinarySearchTree<T> {
+    type Value = T;
+    type Node = TreeNode<T>;
+
+    fn root_node(&self) -> Option<&TreeNode<T>> {
+        self.root.as_deref()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// Dropping the root Box directly would recurse once per level
+impl<T> Drop for BinarySearchTree<T> {
+    fn drop(&mut self) {
+        drop_subtree(self.root.take());
+    }
+}
+
+impl<T> IntoIterator for BinarySearchTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<TreeNode<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter::new(self.root.take(), self.len)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a BinarySearchTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, TreeNode<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinarySearchTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = BinarySearchTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for BinarySearchTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
         }
-        result.push(&self.value);
-        if let Some(right) = &self.right {
-            right.in_order_traversal(result);
+    }
+}
+
+impl<T: Display> RenderNode for TreeNode<T> {
+    // Duplicates held by a node show up as a count
+    fn label(&self) -> String {
+        match self.duplicates.len() {
+            0 => self.value.to_string(),
+            extra => format!("{} ×{}", self.value, extra + 1),
         }
     }
+
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+}
+
+impl<T: Display> Render for BinarySearchTree<T> {
+    type Node = TreeNode<T>;
+
+    fn render_root(&self) -> Option<&Self::Node> {
+        self.root.as_deref()
+    }
+}
+
+// A sensor reading ordered by temperature only, so readings from different
+// sensors can share a key in the tree
+#[derive(Debug)]
+struct SensorReading {
+    temp: i32, // tenths of a degree
+    sensor: &'static str,
+}
+
+impl Ord for SensorReading {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.temp.cmp(&other.temp)
+    }
+}
+
+impl PartialOrd for SensorReading {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SensorReading {
+    fn eq(&self, other: &Self) -> bool {
+        self.temp == other.temp
+    }
+}
+
+impl Eq for SensorReading {}
+
+// Any of the ordered trees can be passed here: they all iterate by
+// reference in ascending order
+fn print_sorted<'a>(label: &str, values: impl IntoIterator<Item = &'a u32>) {
+    let values: Vec<String> = values.into_iter().map(|id| id.to_string()).collect();
+    println!("  {}: {}", label, values.join(" "));
+}
+
+// Minutes since midnight as HH:MM
+fn clock(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+fn window(interval: &Interval<u32>) -> String {
+    format!("{}–{}", clock(interval.start), clock(interval.end))
 }
 
 pub fn demonstrate() {
     println!("Binary Search Tree: Hierarchical sorted structure\n");
 
-    // Create a BST for equipment IDs
+    // Create a BST for equipment IDs (the tree starts out empty)
     println!("Building BST with equipment IDs:");
-    let mut tree = TreeNode::new(50);
-    
-    let equipment_ids = vec![30, 70, 20, 40, 60, 80, 10, 25, 35, 65];
-    
+    let mut tree = BinarySearchTree::new();
+    println!("  Empty tree: {} nodes, is_empty = {}", tree.len(), tree.is_empty());
+
+    let equipment_ids = vec![50, 30, 70, 20, 40, 60, 80, 10, 25, 35, 65, 40];
+
     for &id in &equipment_ids {
-        tree.insert(id);
-        println!("  Inserted: Equipment-{:03}", id);
+        if tree.insert(id) {
+            println!("  Inserted: Equipment-{:03}", id);
+        } else {
+            println!("  Equipment-{:03} already registered, skipped", id);
+        }
     }
+    println!("  Tree size: {} nodes", tree.len());
 
     // Search operations
     println!("\nSearch operations:");
     let search_ids = vec![25, 55, 80, 15];
     for &id in &search_ids {
-        let found = tree.search(&id);
+        let found = tree.contains(&id);
         println!("  Equipment-{:03}: {}", id, if found { "Found ✓" } else { "Not found ✗" });
     }
 
     // In-order traversal (gives sorted output)
     println!("\nIn-order traversal (sorted):");
-    let mut sorted = Vec::new();
-    tree.in_order_traversal(&mut sorted);
     print!("  ");
-    for id in sorted {
+    for id in &tree {
         print!("Equipment-{:03} → ", id);
     }
-    println!("End\n");
+    println!("End");
+
+    // Lazy, non-recursive traversals in every order
+    println!("\nOther traversal orders:");
+    println!("  Pre-order:   {:?}", tree.pre_order().collect::<Vec<_>>());
+    println!("  Post-order:  {:?}", tree.post_order().collect::<Vec<_>>());
+    println!("  Level-order: {:?}", tree.level_order().collect::<Vec<_>>());
+    println!("  Descending:  {:?}", tree.iter().rev().collect::<Vec<_>>());
+    println!(
+        "  Pre-order reversed: {:?}",
+        tree.pre_order().rev().collect::<Vec<_>>()
+    );
+    println!(
+        "  Post-order reversed: {:?}",
+        tree.post_order().rev().collect::<Vec<_>>()
+    );
+    let below_40: Vec<_> = tree.iter().take_while(|&&id| id < 40).collect();
+    println!("  Stop early (IDs below 40): {:?}", below_40);
+
+    // Save the exact shape, then rebuild it from text or from traversals
+    println!("\nSerialization (pre-order, # = empty link):");
+    let saved = tree.serialize();
+    println!("  Saved: {}", saved);
+    let restored = BinarySearchTree::<i32>::deserialize(&saved, DuplicatePolicy::Reject)
+        .expect("serialize output always parses");
+    println!("  Restored with the same shape: {}", restored.serialize() == saved);
+
+    let pre_order: Vec<i32> = tree.pre_order().copied().collect();
+    let post_order: Vec<i32> = tree.post_order().copied().collect();
+    let in_order: Vec<i32> = tree.iter().copied().collect();
+    let rebuilds = [
+        BinarySearchTree::from_pre_and_in_order(&pre_order, &in_order),
+        BinarySearchTree::from_post_and_in_order(&post_order, &in_order),
+    ];
+    for (label, rebuilt) in ["pre-order + in-order", "post-order + in-order"].iter().zip(rebuilds) {
+        let same = rebuilt.is_ok_and(|rebuilt| rebuilt.serialize() == saved);
+        println!("  Rebuilt from {}: same shape = {}", label, same);
+    }
+
+    let mut repeats = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+    repeats.extend([20, 10, 20, 30, 20]);
+    let repeats_text = repeats.serialize();
+    let repeats_back = BinarySearchTree::<i32>::deserialize(&repeats_text, DuplicatePolicy::Keep);
+    println!(
+        "  With duplicates: {} → {} values restored",
+        repeats_text,
+        repeats_back.map_or(0, |tree| tree.len())
+    );
+
+    println!("Rejected inputs:");
+    let bad_text = ["50,30,#", "50,70,#,#,#", "50,x,#,#,#", "50,#,#,10"];
+    for text in bad_text {
+        if let Err(err) = BinarySearchTree::<i32>::deserialize(text, DuplicatePolicy::Reject) {
+            println!("  \"{}\": {}", text, err);
+        }
+    }
+    let mismatched = BinarySearchTree::from_pre_and_in_order(&[20, 10, 30], &[10, 20, 40]);
+    println!("  Mismatched traversals: {}", mismatched.unwrap_err());
+    let unsorted = BinarySearchTree::from_post_and_in_order(&[10, 30, 20], &[30, 10, 20]);
+    println!("  Unsorted in-order: {}", unsorted.unwrap_err());
+
+    // Hierarchy analytics on the same tree
+    println!("\nTree algorithms:");
+    let root = tree.root();
+    let lca = |a, b| lowest_common_ancestor(root, &a, &b).copied();
+    println!("  Height: {}, diameter: {} edges", height(root), diameter(root));
+    println!(
+        "  LCA(10, 35) = {:?}, LCA(65, 80) = {:?}, LCA(10, 99) = {:?}",
+        lca(10, 35),
+        lca(65, 80),
+        lca(10, 99)
+    );
+    println!("  Level 2: {:?}", nodes_at_level(root, 2));
+    let paths = root_to_leaf_paths(root);
+    for (path, sum) in paths.iter().zip(path_sums(root)) {
+        println!("  Path {:?} sums to {}", path, sum);
+    }
+    println!(
+        "  Valid BST: {}, height-balanced: {}",
+        is_valid_bst(root),
+        is_balanced(root)
+    );
+
+    let mirror = mirrored(root);
+    println!(
+        "  Mirrored: valid BST = {}, mirrored twice equals original = {}",
+        is_valid_bst(mirror.root()),
+        same_tree(mirrored(mirror.root()).root(), root)
+    );
+    println!("  Mirrored level 2: {:?}", nodes_at_level(mirror.root(), 2));
+
+    let load = |text| {
+        BinarySearchTree::<i32>::deserialize(text, DuplicatePolicy::Reject)
+            .expect("valid subtree text")
+    };
+    let branch = load("30,20,10,#,#,25,#,#,40,35,#,#,#");
+    let pruned = load("30,20,#,#,40,#,#");
+    println!(
+        "  Contains the 30-branch: {}, contains a pruned copy: {}\n",
+        contains_subtree(root, branch.root()),
+        contains_subtree(root, pruned.root())
+    );
+
+    // Delete operations (removing the root is allowed too)
+    println!("Decommissioning equipment:");
+    let retired_ids = vec![
+        (10, "leaf"),
+        (60, "one child"),
+        (30, "two children"),
+        (50, "root"),
+        (55, "missing"),
+    ];
+    for (id, case) in retired_ids {
+        match tree.remove(&id) {
+            Some(removed) => println!("  Removed Equipment-{:03} ({})", removed, case),
+            None => println!("  Equipment-{:03}: Not found ✗", id),
+        }
+    }
+    println!("  Remaining: {:?}", tree.in_order_traversal());
+
+    // Subtree sizes stay in sync with deletions
+    println!(
+        "  Median of the rest: {:?} (rank of 70: {})",
+        tree.select(tree.len() / 2),
+        tree.rank(&70)
+    );
+
+    // Consume the tree, taking ownership of every ID in sorted order
+    let archived: Vec<i32> = tree.into_iter().collect();
+    println!("  Archived (tree consumed): {:?}\n", archived);
 
     // Example with sensor readings (in tenths of degrees)
     println!("--- Sensor Reading BST (temps in 0.1°C) ---");
-    let mut temp_tree = TreeNode::new(255);
-
-    let temps = vec![232, 281, 225, 268, 240, 295];
+    let temps = vec![255, 232, 281, 225, 268, 240, 295];
     println!("Recording sensor readings:");
     for &temp in &temps {
-        temp_tree.insert(temp);
         println!("  Logged: {:.1}°C", temp as f32 / 10.0);
     }
+    let mut temp_tree: BinarySearchTree<i32> = temps.into_iter().collect();
+
+    println!("\nLate readings arrive:");
+    let late_temps = vec![247, 262];
+    for &temp in &late_temps {
+        println!("  Logged: {:.1}°C", temp as f32 / 10.0);
+    }
+    temp_tree.extend(late_temps);
 
     println!("\nSensor readings in sorted order:");
-    let mut sorted_temps = Vec::new();
-    temp_tree.in_order_traversal(&mut sorted_temps);
-    for temp in sorted_temps {
+    for temp in temp_tree.in_order_traversal() {
+        println!("  {:.1}°C", *temp as f32 / 10.0);
+    }
+
+    // Range queries and ordered navigation
+    println!("\nReadings between 23.0°C and 27.0°C:");
+    for temp in temp_tree.range(230..=270) {
         println!("  {:.1}°C", *temp as f32 / 10.0);
     }
+    let above_26: Vec<_> = temp_tree.range((Bound::Excluded(260), Bound::Unbounded)).collect();
+    println!("  Readings above 26.0°C: {}", above_26.len());
+
+    let to_celsius = |temp: Option<&i32>| match temp {
+        Some(temp) => format!("{:.1}°C", *temp as f32 / 10.0),
+        None => "none".to_string(),
+    };
+    println!("\nOrdered navigation around 25.0°C:");
+    println!("  Nearest at or below (floor):   {}", to_celsius(temp_tree.floor(&250)));
+    println!("  Nearest at or above (ceiling): {}", to_celsius(temp_tree.ceiling(&250)));
+    println!("  Reading before 25.5°C:         {}", to_celsius(temp_tree.predecessor(&255)));
+    println!("  Reading after 25.5°C:          {}", to_celsius(temp_tree.successor(&255)));
+    println!("  Coldest (min):                 {}", to_celsius(temp_tree.min()));
+    println!("  Hottest (max):                 {}", to_celsius(temp_tree.max()));
+
+    // Order statistics from subtree sizes
+    println!("\nPercentiles and ranks ({} readings):", temp_tree.len());
+    for percentile in [50, 95] {
+        // Nearest-rank method: the ceil(p% * n)-th smallest reading
+        let k = (percentile * temp_tree.len()).div_ceil(100) - 1;
+        if let Some(temp) = temp_tree.select(k) {
+            println!("  {}th percentile: {:.1}°C", percentile, *temp as f32 / 10.0);
+        }
+    }
+    println!("  Readings below 25.0°C: {}", temp_tree.rank(&250));
+    println!("  Readings below 28.0°C: {}", temp_tree.rank(&280));
+
+    println!(
+        "  Tree height: {}, balanced: {}, LCA of coldest and hottest: {:?}",
+        height(temp_tree.root()),
+        is_balanced(temp_tree.root()),
+        lowest_common_ancestor(temp_tree.root(), &225, &295)
+    );
+
+    temp_tree.clear();
+    println!(
+        "\nAfter end-of-shift clear: {} readings, is_empty = {}",
+        temp_tree.len(),
+        temp_tree.is_empty()
+    );
+
+    // Multiset mode: equal temperatures from different sensors are all kept
+    println!("--- Duplicate Readings (DuplicatePolicy::Keep) ---");
+    let mut readings = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+    let logged = vec![
+        (255, "TT-101"),
+        (232, "TT-102"),
+        (255, "TT-103"),
+        (240, "TT-104"),
+        (255, "TT-105"),
+        (232, "TT-106"),
+    ];
+    for (temp, sensor) in logged {
+        let new_key = readings.insert(SensorReading { temp, sensor });
+        println!(
+            "  {} reported {:.1}°C{}",
+            sensor,
+            temp as f32 / 10.0,
+            if new_key { "" } else { " (duplicate kept)" }
+        );
+    }
+    let probe = |temp| SensorReading { temp, sensor: "" };
+    println!("  Stored readings: {}", readings.len());
+    // Order statistics count every copy
+    let sensor = |reading: Option<&SensorReading>| reading.map_or("none", |reading| reading.sensor);
+    println!(
+        "  Median reading from {}, hottest from {}",
+        sensor(readings.select(readings.len() / 2)),
+        sensor(readings.iter().next_back())
+    );
+    println!("  Readings at 25.5°C: {}", readings.count(&probe(255)));
+
+    println!("\nIn sorted order (ties in arrival order):");
+    for reading in &readings {
+        println!("  {:.1}°C from {}", reading.temp as f32 / 10.0, reading.sensor);
+    }
+
+    if let Some(reading) = readings.remove_one(&probe(255)) {
+        println!("\nRetracted latest 25.5°C reading from {}", reading.sensor);
+    }
+    let cleared: Vec<_> = readings
+        .remove_all(&probe(232))
+        .into_iter()
+        .map(|reading| reading.sensor)
+        .collect();
+    println!("Discarded all 23.2°C readings: {:?}", cleared);
+    println!(
+        "Remaining: {} readings, {} at 25.5°C\n",
+        readings.len(),
+        readings.count(&probe(255))
+    );
+
+    // Ordered map: attach metadata to each equipment ID
+    println!("--- Equipment Registry (TreeMap: ID → location, model, installed) ---");
+    let mut registry: TreeMap<u32, (&str, &str, &str)> = vec![
+        (40, ("Boiler Room", "HX-200", "2019-03-14")),
+        (20, ("Pump House", "CP-75", "2021-07-02")),
+        (65, ("Tank Farm", "LT-9", "2018-11-20")),
+    ]
+    .into_iter()
+    .collect();
+    registry.extend(vec![(25, ("Pump House", "CP-75", "2021-07-02"))]);
+    registry.insert(70, ("Reactor Hall", "RX-1", "2017-05-09"));
+
+    if let Some(old) = registry.insert(65, ("Tank Farm", "LT-10", "2024-02-01")) {
+        println!("  Equipment-065 replaced (was model {})", old.1);
+    }
+    if let Some(info) = registry.get_mut(&20) {
+        info.0 = "Pump House East";
+    }
+    if let Some((location, model, installed)) = registry.get(&20) {
+        println!("  Equipment-020: {} / {} / installed {}", location, model, installed);
+    }
+    println!("  Contains Equipment-035? {}", registry.contains_key(&35));
+
+    // Entry API, mirroring HashMap::entry
+    for id in [35, 40] {
+        match registry.entry(id) {
+            Entry::Occupied(mut entry) => {
+                let old = entry.insert(("Boiler Room", "HX-250", "2025-01-10"));
+                println!(
+                    "  Equipment-{:03} upgraded: {} → {}",
+                    entry.key(),
+                    old.1,
+                    entry.get().1
+                );
+            }
+            Entry::Vacant(entry) => {
+                println!("  Registering Equipment-{:03}", entry.key());
+                entry.insert(("Separator Bay", "SP-3", "2022-09-30"));
+            }
+        }
+    }
+    if let Entry::Occupied(entry) = registry.entry(25) {
+        let (location, _, _) = entry.remove();
+        println!("  Equipment-025 retired from {}", location);
+    }
+
+    println!("\nRegistry in ID order ({} entries):", registry.len());
+    for (id, (location, model, installed)) in &registry {
+        println!("  Equipment-{:03}: {:<16} {:<7} {}", id, location, model, installed);
+    }
+
+    let mut visit_counts: TreeMap<&str, u32> = TreeMap::new();
+    println!("\nMaintenance visits per location:");
+    for (_, (location, _, _)) in registry.iter() {
+        *visit_counts.entry(location).or_default() += 1;
+    }
+    visit_counts.entry("Control Room").or_insert(0);
+    visit_counts
+        .entry("Tank Farm")
+        .and_modify(|visits| *visits += 2)
+        .or_insert_with(|| 1);
+    for (location, visits) in &visit_counts {
+        println!("  {}: {} visit(s)", location, visits);
+    }
+    println!("  Empty map? {}\n", visit_counts.is_empty());
+
+    // Sorted input degrades a plain BST into a linked list; AVL stays balanced
+    println!("\n--- AVL Tree (self-balancing BST) ---");
+    let mut avl_tree = AvlTree::new();
+
+    println!("Inserting equipment IDs in sorted order:");
+    for id in (10..=150).step_by(10) {
+        avl_tree.insert(id);
+    }
+    println!("  Inserted {} IDs: {:?}", avl_tree.len(), avl_tree.in_order_traversal());
+    println!(
+        "  AVL height: {} (a plain BST would have height {})",
+        avl_tree.height(),
+        avl_tree.len()
+    );
+
+    println!("\nDecommissioning equipment:");
+    for id in [40, 80, 10, 999] {
+        match avl_tree.remove(&id) {
+            Some(removed) => println!("  Removed: Equipment-{:03}", removed),
+            None => println!("  Equipment-{:03}: Not found ✗", id),
+        }
+    }
+    println!("  Remaining: {:?}", avl_tree.in_order_traversal());
+    println!("  AVL height: {}", avl_tree.height());
+    println!("  Contains Equipment-080? {}", avl_tree.contains(&80));
+    println!("  Invariants hold: {}", avl_tree.check_invariants().is_ok());
+
+    // Red-black: looser balance than AVL, so fewer rotations on heavy writes
+    println!("\n--- Red-Black Tree (left-leaning) ---");
+    let mut rb_tree: RedBlackTree<i32> = (10..=80).step_by(10).collect();
+    rb_tree.extend([25, 35, 65]);
+    println!("Readings logged: {}", rb_tree.len());
+    println!("  Sorted: {:?}", rb_tree.in_order_traversal());
+    println!(
+        "  Height: {} (valid: {})",
+        rb_tree.height(),
+        rb_tree.check_invariants().is_ok()
+    );
+    println!("  Range of IDs: {:?} .. {:?}", rb_tree.min(), rb_tree.max());
+
+    for id in [30, 10, 80, 99] {
+        match rb_tree.remove(&id) {
+            Some(removed) => println!("  Removed: Equipment-{:03}", removed),
+            None => println!("  Equipment-{:03}: Not found ✗", id),
+        }
+    }
+    println!("  Invariants hold: {}", rb_tree.check_invariants().is_ok());
+    println!("  Contains Equipment-065? {}", rb_tree.contains(&65));
+    print!("  Descending:");
+    for id in rb_tree.iter().rev() {
+        print!(" {}", id);
+    }
+    println!();
+
+    // Same ordered surface as the plain BST, backed by stored subtree sizes
+    println!(
+        "  Floor/ceiling of 45: {:?} / {:?}",
+        rb_tree.floor(&45),
+        rb_tree.ceiling(&45)
+    );
+    println!(
+        "  Neighbours of 50: {:?} < 50 < {:?}",
+        rb_tree.predecessor(&50),
+        rb_tree.successor(&50)
+    );
+    println!("  3rd smallest: {:?}, rank of 60: {}", rb_tree.select(2), rb_tree.rank(&60));
+    println!("  IDs in 25..=60: {:?}", rb_tree.range(25..=60).collect::<Vec<_>>());
+    println!("  Pre-order: {:?}", rb_tree.pre_order().collect::<Vec<_>>());
+    println!("  Post-order: {:?}", rb_tree.post_order().collect::<Vec<_>>());
+    println!("  Level-order: {:?}", rb_tree.level_order().collect::<Vec<_>>());
+    let remaining: Vec<i32> = rb_tree.into_iter().collect();
+    println!("  Drained by value: {:?}", remaining);
+
+    let mut rb_tree: RedBlackTree<i32> = remaining.into_iter().collect();
+    rb_tree.clear();
+    println!("  After clear: is_empty = {}", rb_tree.is_empty());
+
+    // Wide nodes: ORDER = 5 means up to 4 keys and 5 children per node
+    println!("\n--- B-Tree (ORDER = 5) ---");
+    let mut registry = BTree::<u32, 5>::from_sorted((1..=40).map(|n| n * 5))
+        .expect("IDs are generated in ascending order");
+    println!("Bulk-loaded {} sorted equipment IDs", registry.len());
+    println!(
+        "  Height: {} (valid: {})",
+        registry.height(),
+        registry.check_invariants().is_ok()
+    );
+    match BTree::<u32, 5>::from_sorted([10, 30, 20]) {
+        Ok(_) => println!("  Unsorted bulk-load accepted ✗"),
+        Err(err) => println!("  Unsorted bulk-load rejected: {}", err),
+    }
+
+    for id in [12, 47, 101, 15] {
+        let inserted = registry.insert(id);
+        println!("  Insert Equipment-{:03}: {}", id, if inserted { "added" } else { "duplicate" });
+    }
+    for id in [100, 5, 50, 55, 60, 65, 999] {
+        match registry.remove(&id) {
+            Some(removed) => println!("  Removed: Equipment-{:03}", removed),
+            None => println!("  Equipment-{:03}: Not found ✗", id),
+        }
+    }
+    println!("  Still valid after the removals? {}", registry.check_invariants().is_ok());
+    println!("  Contains Equipment-047? {}", registry.contains(&47));
+    println!("  Range of IDs: {:?} .. {:?}", registry.min(), registry.max());
+    let batch: Vec<_> = registry.range(40..=80).collect();
+    println!("  IDs in 40..=80: {:?}", batch);
+    let newest: Vec<_> = registry.iter().rev().take(3).collect();
+    println!("  Newest three: {:?}", newest);
+    print_sorted("B-tree IDs under 40", registry.range(..40));
+
+    // Same ordered-iteration contract as the binary trees
+    let small_btree: BTree<u32, 3> = [30, 10, 20, 40].into_iter().collect();
+    let small_rb: RedBlackTree<u32> = [30, 10, 20, 40].into_iter().collect();
+    print_sorted("BTree<_, 3>", &small_btree);
+    print_sorted("RedBlackTree", &small_rb);
+    println!("  First of B-tree: {:?}", small_btree.in_order_traversal().first());
+
+    // Snapshot at the start of a shift, diff at the end
+    println!("\n--- Persistent Tree (shift snapshots) ---");
+    let start_of_shift: PersistentTree<u32> =
+        [50, 30, 70, 20, 40, 60, 80, 10, 25, 35, 45, 55, 65, 75, 90].into_iter().collect();
+    let mut current = start_of_shift.clone();
+    for id in [42, 95] {
+        current = current.insert(id);
+    }
+    for id in [30, 75, 999] {
+        current = current.remove(&id);
+    }
+    println!("  Start of shift: {:?}", start_of_shift.in_order_traversal());
+    println!("  End of shift:   {:?}", current.in_order_traversal());
+    println!(
+        "  Snapshot untouched: {} IDs, contains 30? {}",
+        start_of_shift.len(),
+        start_of_shift.contains(&30)
+    );
+
+    let changes = diff(&start_of_shift, &current);
+    println!("  Added:   {:?}", changes.added);
+    println!("  Removed: {:?}", changes.removed);
+    println!(
+        "  Diff opened {} nodes and skipped {} shared ones",
+        changes.visited, changes.shared
+    );
+    let readded = current.insert(50);
+    let unchanged = diff(&current, &readded);
+    println!(
+        "  Re-adding an existing ID: {} change(s), empty tree? {}",
+        unchanged.added.len() + unchanged.removed.len(),
+        PersistentTree::<u32>::new().is_empty()
+    );
+
+    // Planned outages as [start, end) in minutes since midnight
+    println!("\n--- Interval Tree (maintenance windows) ---");
+    let at = |hour: u32, minute: u32| hour * 60 + minute;
+    let planned = [
+        (at(8, 0), at(10, 0), "Reactor", "catalyst check"),
+        (at(9, 30), at(11, 0), "Heat Exchanger", "tube cleaning"),
+        (at(13, 0), at(15, 0), "Compressor", "seal swap"),
+        (at(14, 30), at(17, 0), "Reactor", "valve test"),
+        (at(15, 0), at(16, 0), "Compressor", "vibration survey"),
+        (at(16, 0), at(18, 0), "Separator", "inspection"),
+        (at(12, 0), at(14, 0), "Storage Tank", "level sensor"),
+        (at(15, 30), at(16, 30), "Reactor", "relief valve"),
+        (at(19, 0), at(18, 0), "Separator", "flare test"),
+    ];
+    let mut outages = IntervalTree::new();
+    for (start, end, equipment, job) in planned {
+        // A window that ends before it starts is a typo, not an outage
+        match Interval::try_new(start, end) {
+            Some(interval) => outages.insert(interval, (equipment, job)),
+            None => println!(
+                "  Skipped {} {}: {} is not before {}",
+                equipment,
+                job,
+                clock(start),
+                clock(end)
+            ),
+        }
+    }
+    println!("  {} windows, tree height {}", outages.len(), outages.height());
+
+    let afternoon = Interval::new(at(14, 0), at(16, 0));
+    println!("  Overlapping {}:", window(&afternoon));
+    for (interval, (equipment, job)) in outages.overlapping(&afternoon) {
+        println!("    {} {} ({})", window(interval), equipment, job);
+    }
+    // Half-open: the Compressor survey ending at 16:00 is over by 16:00
+    for time in [at(15, 45), at(16, 0)] {
+        let down: Vec<&str> = outages
+            .stab(time)
+            .into_iter()
+            .map(|(_, (equipment, _))| *equipment)
+            .collect();
+        println!("  Down at {}: {:?}", clock(time), down);
+    }
+
+    // Two windows on the same equipment that overlap are a conflict;
+    // each pair is reported once, from its earlier window
+    let conflicts = |tree: &IntervalTree<u32, (&str, &str)>| {
+        let mut found = Vec::new();
+        for (interval, entry) in tree.entries() {
+            for (other, other_entry) in tree.overlapping(interval) {
+                if entry.0 == other_entry.0 && (interval, entry.1) < (other, other_entry.1) {
+                    found.push(format!(
+                        "{}: {} {} vs {} {}",
+                        entry.0,
+                        window(interval),
+                        entry.1,
+                        window(other),
+                        other_entry.1
+                    ));
+                }
+            }
+        }
+        found
+    };
+    println!("  Conflicts on the same equipment:");
+    for conflict in conflicts(&outages) {
+        println!("    {}", conflict);
+    }
+
+    let valve_test = Interval::new(at(14, 30), at(17, 0));
+    println!(
+        "  Valve test overlaps {}: {}, still running at 16:59: {}",
+        window(&afternoon),
+        valve_test.overlaps(&afternoon),
+        valve_test.contains(&at(16, 59))
+    );
+    let moved = outages.remove(&valve_test, &("Reactor", "valve test"));
+    println!("  Rescheduling {:?} to 18:00–20:30", moved.map(|(_, job)| job));
+    outages.insert(Interval::new(at(18, 0), at(20, 30)), ("Reactor", "valve test"));
+    println!("  Conflicts left: {}", conflicts(&outages).len());
+    println!("  Invariants hold: {}", outages.check_invariants().is_ok());
+    println!("  Empty schedule? {}", IntervalTree::<u32, ()>::new().is_empty());
+
+    // Draw the real shapes so balancing problems are visible
+    println!("\n--- Tree Shapes (ASCII art / Graphviz) ---");
+    let ids = [10, 20, 30, 40, 50, 60, 70];
+    let mut plain: BinarySearchTree<u32> = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+    plain.extend(ids);
+    plain.insert(30);
+    let mut balanced = AvlTree::new();
+    for id in ids {
+        balanced.insert(id);
+    }
+    println!("Plain BST, sorted inserts (note the growing balance factors):");
+    print!("{}", plain.to_ascii());
+    println!(
+        "  Height {} for {} distinct IDs, height-balanced: {}",
+        height(plain.root()),
+        ids.len(),
+        is_balanced(plain.root())
+    );
+    println!("AVL tree, same inserts:");
+    print!("{}", balanced.to_ascii());
+    println!("B-tree (ORDER = 3):");
+    print!("{}", small_btree.to_ascii());
+    println!("Persistent snapshot without 50 (its successor takes the root):");
+    print!("{}", start_of_shift.remove(&50).to_ascii());
+    let registry_keys: TreeMap<u32, &str> = [(20, "Pump"), (10, "Valve")].into_iter().collect();
+    println!("TreeMap keys:");
+    print!("{}", registry_keys.to_ascii());
+    println!("Empty tree: {}", RedBlackTree::<u32>::new().to_ascii().trim_end());
+
+    let colored: RedBlackTree<u32> = [40, 20, 60, 10, 30].into_iter().collect();
+    println!("\nRed-black tree as Graphviz DOT (pipe into `dot -Tpng`):");
+    print!("{}", colored.to_dot("red_black"));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random keys without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // Every node's size must be its own copies plus both subtree sizes
+    fn sizes_hold<T>(tree: &BinarySearchTree<T>) -> bool {
+        let mut stack: Vec<&TreeNode<T>> = tree.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if node.size != node.count() + size(&node.left) + size(&node.right) {
+                return false;
+            }
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+        size(&tree.root) == tree.len
+    }
+
+    #[test]
+    fn select_and_rank_follow_edits() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let mut tree = BinarySearchTree::new();
+        let mut model = BTreeSet::new();
+        for _ in 0..1500 {
+            let key = next(&mut state) % 120;
+            if next(&mut state).is_multiple_of(3) {
+                assert_eq!(tree.remove(&key), model.take(&key));
+            } else {
+                assert_eq!(tree.insert(key), model.insert(key));
+            }
+            assert!(sizes_hold(&tree));
+            for (k, key) in model.iter().enumerate() {
+                assert_eq!(tree.select(k), Some(key));
+                assert_eq!(tree.rank(key), k);
+            }
+            assert_eq!(tree.select(model.len()), None);
+            let probe = next(&mut state) % 130;
+            assert_eq!(tree.rank(&probe), model.range(..probe).count());
+        }
+    }
+
+    fn value_at<T: Copy>(link: &Link<T>) -> Option<T> {
+        link.as_ref().map(|node| node.value)
+    }
+
+    //         50
+    //       /    \
+    //     30      70
+    //    /  \    /  \
+    //   20  40  60  80
+    //             \
+    //              65
+    fn plant_ids() -> BinarySearchTree<u32> {
+        [50, 30, 70, 20, 40, 60, 80, 65].into_iter().collect()
+    }
+
+    #[test]
+    fn remove_handles_every_shape() {
+        let mut tree = plant_ids();
+
+        // Leaf: its parent simply loses the child
+        assert_eq!(tree.remove(&20), Some(20));
+        let thirty = tree.root.as_ref().unwrap().left.as_ref().unwrap();
+        assert_eq!((thirty.value, value_at(&thirty.left)), (30, None));
+        assert!(is_valid_bst(tree.root()));
+
+        // One child: 65 moves up into 60's place
+        assert_eq!(tree.remove(&60), Some(60));
+        let seventy = tree.root.as_ref().unwrap().right.as_ref().unwrap();
+        assert_eq!((value_at(&seventy.left), value_at(&seventy.right)), (Some(65), Some(80)));
+        assert!(is_valid_bst(tree.root()));
+
+        // Two children: the in-order successor (80, the smallest on the
+        // right) takes 70's place
+        assert_eq!(tree.remove(&70), Some(70));
+        let eighty = tree.root.as_ref().unwrap().right.as_ref().unwrap();
+        assert_eq!(eighty.value, 80);
+        assert_eq!((value_at(&eighty.left), value_at(&eighty.right)), (Some(65), None));
+        assert!(is_valid_bst(tree.root()));
+
+        // Root: its successor 65 becomes the new root
+        assert_eq!(tree.remove(&50), Some(50));
+        assert_eq!(value_at(&tree.root), Some(65));
+        assert!(is_valid_bst(tree.root()));
+        assert!(sizes_hold(&tree));
+
+        // Missing key: nothing changes
+        assert_eq!(tree.remove(&55), None);
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.in_order_traversal(), [&30, &40, &65, &80]);
+
+        // Removing the last root leaves an empty tree that still works
+        for id in [65, 30, 80, 40] {
+            assert_eq!(tree.remove(&id), Some(id));
+            assert!(is_valid_bst(tree.root()));
+        }
+        assert!(tree.is_empty() && tree.root().is_none());
+        assert_eq!(tree.remove(&40), None);
+        assert!(tree.insert(40));
+    }
+
+    #[test]
+    fn random_removals_match_a_btreeset() {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        let mut tree = BinarySearchTree::new();
+        let mut model = BTreeSet::new();
+        for step in 0..3000 {
+            let key = next(&mut state) % 200;
+            // Fill up first, then mostly remove so the tree empties again
+            if next(&mut state) % 10 < if step < 1500 { 7 } else { 2 } {
+                assert_eq!(tree.insert(key), model.insert(key));
+            } else {
+                assert_eq!(tree.remove(&key), model.take(&key));
+            }
+            assert!(is_valid_bst(tree.root()));
+            assert_eq!(tree.len(), model.len());
+            assert!(tree.iter().eq(model.iter()));
+        }
+        assert!(sizes_hold(&tree));
+    }
+
+    #[test]
+    fn collect_extend_and_clear() {
+        let mut tree: BinarySearchTree<u32> = [40, 10, 40, 30].into_iter().collect();
+        assert_eq!((tree.len(), tree.is_empty()), (3, false));
+        assert_eq!(tree.in_order_traversal(), [&10, &30, &40]);
+
+        // Extend skips values already present, like repeated inserts
+        tree.extend([30, 20, 50, 20]);
+        let model: BTreeSet<u32> = [40, 10, 40, 30, 30, 20, 50, 20].into_iter().collect();
+        assert_eq!(tree.len(), model.len());
+        assert!(tree.iter().eq(model.iter()));
+        assert!((0..60).all(|id| tree.contains(&id) == model.contains(&id)));
+        assert!(sizes_hold(&tree));
+
+        tree.clear();
+        assert_eq!((tree.len(), tree.is_empty(), tree.iter().next()), (0, true, None));
+        assert!(!tree.contains(&10));
+        // A cleared tree is as good as a new one
+        tree.extend([2, 1]);
+        assert_eq!(tree.in_order_traversal(), [&1, &2]);
+        assert!(BinarySearchTree::<u32>::new().is_empty());
+    }
+
+    #[test]
+    fn navigation_matches_a_btreeset() {
+        let mut state = 0xD1B5_4A32_D192_ED03;
+        let draws: Vec<u64> = (0..300).map(|_| next(&mut state) % 1000).collect();
+        let tree: BinarySearchTree<u64> = draws.iter().copied().collect();
+        let model: BTreeSet<u64> = draws.into_iter().collect();
+        assert_eq!((tree.min(), tree.max()), (model.first(), model.last()));
+        for probe in 0..1010 {
+            let above = (Bound::Excluded(probe), Bound::Unbounded);
+            assert_eq!(tree.floor(&probe), model.range(..=probe).next_back());
+            assert_eq!(tree.ceiling(&probe), model.range(probe..).next());
+            assert_eq!(tree.predecessor(&probe), model.range(..probe).next_back());
+            assert_eq!(tree.successor(&probe), model.range(above).next());
+        }
+        for _ in 0..300 {
+            let (a, b) = (next(&mut state) % 1010, next(&mut state) % 1010);
+            let (low, high) = (a.min(b), a.max(b));
+            let open = (Bound::Excluded(low), Bound::Excluded(high));
+            assert!(tree.range(low..high).eq(model.range(low..high)));
+            assert!(tree.range(low..=high).eq(model.range(low..=high)));
+            assert!(tree.range(low..).eq(model.range(low..)));
+            assert!(tree.range(..=high).eq(model.range(..=high)));
+            assert!(tree.range(open).eq(model.range(open)));
+        }
+        assert!(tree.range(..).eq(model.iter()));
+
+        let empty = BinarySearchTree::<u64>::new();
+        assert_eq!((empty.min(), empty.floor(&5), empty.successor(&5)), (None, None, None));
+        assert_eq!(empty.range(..).next(), None);
+    }
+
+    #[test]
+    fn range_yields_every_copy() {
+        let mut tree = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+        tree.extend([30, 10, 20, 20, 30]);
+        assert!(tree.range(15..=30).eq([&20, &20, &30, &30]));
+        assert!(tree.range(20..30).eq([&20, &20]));
+        assert_eq!((tree.floor(&25), tree.successor(&20)), (Some(&20), Some(&30)));
+    }
+
+    // Taking `split` values from the front and the rest from the back
+    // must give the same sequence as walking forwards, for every split
+    fn meets_in_the_middle<'a, I>(walk: impl Fn() -> I, expected: &[u32])
+    where
+        I: DoubleEndedIterator<Item = &'a u32> + ExactSizeIterator,
+    {
+        assert!(walk().copied().eq(expected.iter().copied()));
+        assert!(walk().rev().copied().eq(expected.iter().rev().copied()));
+        for split in 0..=expected.len() {
+            let mut values = walk();
+            let mut seen: Vec<u32> = values.by_ref().take(split).copied().collect();
+            assert_eq!(values.len(), expected.len() - split);
+            let mut back: Vec<u32> = values.by_ref().rev().copied().collect();
+            back.reverse();
+            seen.extend(back);
+            assert_eq!(seen, expected);
+            assert_eq!((values.next(), values.next_back()), (None, None));
+        }
+    }
+
+    #[test]
+    fn traversals_work_from_both_ends() {
+        let tree = plant_ids();
+        meets_in_the_middle(|| tree.iter(), &[20, 30, 40, 50, 60, 65, 70, 80]);
+        meets_in_the_middle(|| tree.pre_order(), &[50, 30, 20, 40, 70, 60, 65, 80]);
+        meets_in_the_middle(|| tree.post_order(), &[20, 40, 30, 65, 60, 80, 70, 50]);
+        assert!(tree.level_order().eq([&50, &30, &70, &20, &40, &60, &80, &65]));
+        assert_eq!(tree.level_order().len(), 8);
+
+        let mut copies = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+        copies.extend([2, 1, 2, 3, 2]);
+        meets_in_the_middle(|| copies.iter(), &[1, 2, 2, 2, 3]);
+        meets_in_the_middle(|| copies.pre_order(), &[2, 2, 2, 1, 3]);
+        meets_in_the_middle(|| copies.post_order(), &[1, 3, 2, 2, 2]);
+        assert!(copies.into_iter().eq([1, 2, 2, 2, 3]));
+
+        let empty = BinarySearchTree::<u32>::new();
+        meets_in_the_middle(|| empty.iter(), &[]);
+        assert_eq!(empty.into_iter().next(), None);
+    }
+
+    #[test]
+    fn into_iter_hands_over_ownership_in_order() {
+        let names: BinarySearchTree<String> =
+            ["pump", "valve", "tank", "boiler"].map(String::from).into_iter().collect();
+        let mut owned = names.into_iter();
+        assert_eq!(owned.len(), 4);
+        assert_eq!(owned.next().as_deref(), Some("boiler"));
+        assert_eq!(owned.len(), 3);
+        // Dropping a half-used iterator frees the rest
+        drop(owned);
+    }
+
+    // Sorted keys inserted one at a time would take quadratic time, so the
+    // right-leaning chain is linked up directly, from the bottom
+    fn sorted_chain(depth: u32) -> BinarySearchTree<u32> {
+        let mut root: Link<u32> = None;
+        for value in (0..depth).rev() {
+            let mut node = TreeNode::new(value);
+            node.size += size(&root);
+            node.right = root;
+            root = Some(Box::new(node));
+        }
+        BinarySearchTree {
+            root,
+            len: depth as usize,
+            policy: DuplicatePolicy::Reject,
+        }
+    }
+
+    // One stack frame per level would overflow the test thread's stack
+    #[test]
+    fn degenerate_chains_walk_and_drop_without_recursion() {
+        const DEPTH: u32 = 200_000;
+        let tree = sorted_chain(DEPTH);
+        assert!(sizes_hold(&tree));
+        assert!(tree.iter().copied().eq(0..DEPTH));
+        assert!(tree.iter().rev().copied().eq((0..DEPTH).rev()));
+        assert!(tree.pre_order().copied().eq(0..DEPTH));
+        assert!(tree.post_order().copied().eq((0..DEPTH).rev()));
+        assert_eq!(tree.level_order().count(), DEPTH as usize);
+        let last = DEPTH - 1;
+        assert_eq!((tree.max(), tree.select(last as usize)), (Some(&last), Some(&last)));
+        assert!(tree.range(DEPTH - 3..).eq([&(DEPTH - 3), &(DEPTH - 2), &(DEPTH - 1)]));
+        drop(tree);
+
+        let mut tree = sorted_chain(DEPTH);
+        tree.clear();
+        assert!(tree.is_empty());
+
+        let mut drained = sorted_chain(DEPTH).into_iter();
+        assert_eq!(drained.nth(10), Some(10));
+        drop(drained);
+        assert!(sorted_chain(DEPTH).into_iter().eq(0..DEPTH));
+    }
+
+    fn sensors<'a>(values: impl Iterator<Item = &'a SensorReading>) -> Vec<&'static str> {
+        values.map(|reading| reading.sensor).collect()
+    }
+
+    #[test]
+    fn keep_policy_stores_every_copy_in_arrival_order() {
+        let reading = |temp, sensor| SensorReading { temp, sensor };
+        let mut tree = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+        assert!(tree.insert(reading(255, "a")));
+        assert!(tree.insert(reading(232, "b")));
+        assert!(!tree.insert(reading(255, "c")));
+        assert!(!tree.insert(reading(255, "d")));
+        assert!(!tree.insert(reading(232, "e")));
+        assert_eq!(tree.len(), 5);
+        assert_eq!((tree.count(&reading(255, "")), tree.count(&reading(1, ""))), (3, 0));
+        assert!(sizes_hold(&tree));
+        assert_eq!(sensors(tree.iter()), ["b", "e", "a", "c", "d"]);
+        assert_eq!(sensors(tree.iter().rev()), ["d", "c", "a", "e", "b"]);
+        assert_eq!(sensors((0..5).filter_map(|k| tree.select(k))), ["b", "e", "a", "c", "d"]);
+
+        // remove_one takes the newest copy; the node stays while copies remain
+        assert_eq!(tree.remove_one(&reading(255, "")).map(|r| r.sensor), Some("d"));
+        assert_eq!((tree.len(), tree.count(&reading(255, ""))), (4, 2));
+        assert!(sizes_hold(&tree));
+        assert_eq!(tree.remove(&reading(255, "")).map(|r| r.sensor), Some("c"));
+        assert_eq!(tree.remove_one(&reading(255, "")).map(|r| r.sensor), Some("a"));
+        assert!(!tree.contains(&reading(255, "")));
+        assert_eq!(tree.remove_one(&reading(255, "")).map(|r| r.sensor), None);
+        assert_eq!(tree.len(), 2);
+        assert!(sizes_hold(&tree));
+
+        // remove_all takes the whole node, copies in arrival order
+        tree.insert(reading(240, "f"));
+        assert_eq!(sensors(tree.remove_all(&reading(232, "")).iter()), ["b", "e"]);
+        assert!(tree.remove_all(&reading(232, "")).is_empty());
+        assert_eq!((tree.len(), tree.count(&reading(232, ""))), (1, 0));
+        assert!(sizes_hold(&tree));
+        assert_eq!(sensors(tree.iter()), ["f"]);
+    }
+
+    #[test]
+    fn reject_policy_drops_equal_values() {
+        let mut tree = BinarySearchTree::new();
+        assert!(tree.insert(SensorReading { temp: 255, sensor: "a" }));
+        assert!(!tree.insert(SensorReading { temp: 255, sensor: "b" }));
+        assert_eq!((tree.len(), tree.count(&SensorReading { temp: 255, sensor: "" })), (1, 1));
+        assert!(sizes_hold(&tree));
+        assert_eq!(sensors(tree.iter()), ["a"]);
+        assert_eq!(tree.remove_all(&SensorReading { temp: 255, sensor: "" }).len(), 1);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn select_and_rank_count_duplicates() {
+        let mut tree = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+        tree.extend([20, 10, 20, 30, 20, 10]);
+        assert!(sizes_hold(&tree));
+        let selected: Vec<Option<&i32>> = (0..7).map(|k| tree.select(k)).collect();
+        let copies = [&10, &10, &20, &20, &20, &30];
+        assert_eq!(selected, copies.map(Some).into_iter().chain([None]).collect::<Vec<_>>());
+        assert_eq!((tree.rank(&10), tree.rank(&20), tree.rank(&30), tree.rank(&35)), (0, 2, 5, 6));
+
+        tree.remove_one(&20);
+        assert!(sizes_hold(&tree));
+        assert_eq!((tree.rank(&30), tree.select(4)), (4, Some(&30)));
+        tree.remove_all(&10);
+        assert!(sizes_hold(&tree));
+        assert_eq!((tree.rank(&20), tree.select(0), tree.select(3)), (0, Some(&20), None));
+    }
 }
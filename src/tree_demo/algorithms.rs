@@ -0,0 +1,378 @@
+// TREE ALGORITHMS (Classic Helpers on TreeNode)
+// Free functions over a Link, so they work on a whole tree
+// (BinarySearchTree::root) or on any subtree inside one
+// - lowest_common_ancestor: deepest node with both keys below it
+// - height / diameter / nodes_at_level: shape measurements
+// - root_to_leaf_paths / path_sums: every path from the root to a leaf
+// - is_valid_bst / is_balanced: structural checks
+// - mirrored: left and right swapped at every node
+// - same_tree / contains_subtree: structural equality
+// Every walk uses an explicit stack or a level loop, so degenerate trees
+// of any depth work
+// Use cases: hierarchy analytics, sanity checks on loaded trees
+
+use std::iter::Sum;
+
+use super::iter::drop_subtree;
+use super::ordered::OrderedNode;
+use super::{size, Link, TreeNode};
+
+// Both keys must be in the tree; the BST ordering tells us which way to
+// go until the keys end up on different sides (or one is the node itself)
+pub fn lowest_common_ancestor<'a, T: Ord>(root: &'a Link<T>, a: &T, b: &T) -> Option<&'a T> {
+    let mut current = root.as_ref()?;
+    current.search(a)?;
+    current.search(b)?;
+    loop {
+        if a < &current.value && b < &current.value {
+            current = current.left.as_ref()?;
+        } else if a > &current.value && b > &current.value {
+            current = current.right.as_ref()?;
+        } else {
+            return Some(&current.value);
+        }
+    }
+}
+
+// Children of a node, left first
+fn children<T>(node: &TreeNode<T>) -> impl Iterator<Item = &TreeNode<T>> {
+    node.left.iter().chain(&node.right).map(|child| &**child)
+}
+
+// Post-order fold with an explicit stack: `combine` gets each node with
+// the results for its left and right subtrees (`empty` for a missing one)
+fn fold_post_order<T, R: Copy>(
+    link: &Link<T>,
+    empty: R,
+    combine: impl Fn(&TreeNode<T>, R, R) -> R,
+) -> R {
+    // (node, whether its subtrees are done); finished subtrees leave
+    // their results on `done`, left below right
+    let mut stack: Vec<(&TreeNode<T>, bool)> = link.iter().map(|node| (&**node, false)).collect();
+    let mut done = Vec::new();
+    while let Some((node, subtrees_done)) = stack.pop() {
+        if subtrees_done {
+            let right = if node.right.is_some() { done.pop().unwrap() } else { empty };
+            let left = if node.left.is_some() { done.pop().unwrap() } else { empty };
+            done.push(combine(node, left, right));
+        } else {
+            stack.push((node, true));
+            stack.extend(node.right.iter().chain(&node.left).map(|child| (&**child, false)));
+        }
+    }
+    done.pop().unwrap_or(empty)
+}
+
+// Nodes on the longest root-to-leaf path (0 for an empty tree)
+pub fn height<T>(link: &Link<T>) -> usize {
+    let mut current: Vec<&TreeNode<T>> = link.iter().map(|node| &**node).collect();
+    let mut height = 0;
+    while !current.is_empty() {
+        height += 1;
+        current = current.into_iter().flat_map(children).collect();
+    }
+    height
+}
+
+// Edges on the longest path between any two nodes
+pub fn diameter<T>(link: &Link<T>) -> usize {
+    // (height, longest path in edges) of each subtree
+    let (_, best) = fold_post_order(link, (0, 0), |_, (left_height, left_best), right| {
+        let (right_height, right_best) = right;
+        // The longest path through this node joins the two deepest branches
+        let through_node = left_height + right_height;
+        (
+            1 + left_height.max(right_height),
+            through_node.max(left_best).max(right_best),
+        )
+    });
+    best
+}
+
+// Values at depth `level` (the root is level 0), left to right
+pub fn nodes_at_level<T>(link: &Link<T>, level: usize) -> Vec<&T> {
+    let mut current: Vec<&TreeNode<T>> = link.iter().map(|node| &**node).collect();
+    for _ in 0..level {
+        current = current.into_iter().flat_map(children).collect();
+    }
+    current.into_iter().map(|node| &node.value).collect()
+}
+
+pub fn root_to_leaf_paths<T>(link: &Link<T>) -> Vec<Vec<&T>> {
+    // (node, its depth); `path` is cut back to a node's depth before the
+    // node is added, so it always runs from the root to the current node
+    let mut stack: Vec<(&TreeNode<T>, usize)> = link.iter().map(|node| (&**node, 0)).collect();
+    let mut path = Vec::new();
+    let mut paths = Vec::new();
+    while let Some((node, depth)) = stack.pop() {
+        path.truncate(depth);
+        path.push(&node.value);
+        if node.left.is_none() && node.right.is_none() {
+            paths.push(path.clone());
+        } else {
+            let below = node.right.iter().chain(&node.left);
+            stack.extend(below.map(|child| (&**child, depth + 1)));
+        }
+    }
+    paths
+}
+
+// Sum of each root-to-leaf path, in the same order as root_to_leaf_paths
+pub fn path_sums<'a, T: Sum<&'a T>>(link: &'a Link<T>) -> Vec<T> {
+    root_to_leaf_paths(link)
+        .into_iter()
+        .map(|path| path.into_iter().sum())
+        .collect()
+}
+
+// Checks the ordering every BST operation relies on (and the stored
+// subtree sizes), so hand-built or mirrored trees can be vetted
+pub fn is_valid_bst<T: Ord>(link: &Link<T>) -> bool {
+    // Each node with the bounds its ancestors impose
+    let mut stack: Vec<(&TreeNode<T>, Option<&T>, Option<&T>)> =
+        link.iter().map(|node| (&**node, None, None)).collect();
+    while let Some((node, lower, upper)) = stack.pop() {
+        let in_bounds = lower.is_none_or(|lower| node.value > *lower)
+            && upper.is_none_or(|upper| node.value < *upper);
+        let size_ok = node.size == node.count() + size(&node.left) + size(&node.right);
+        if !(in_bounds && size_ok && node.duplicates.iter().all(|copy| *copy == node.value)) {
+            return false;
+        }
+        stack.extend(node.right.iter().map(|right| (&**right, Some(&node.value), upper)));
+        stack.extend(node.left.iter().map(|left| (&**left, lower, Some(&node.value))));
+    }
+    true
+}
+
+// Height-balanced: at every node the two subtree heights differ by <= 1
+pub fn is_balanced<T>(link: &Link<T>) -> bool {
+    // Height of a balanced subtree, or None once any subtree is not
+    let balanced_height = fold_post_order(link, Some(0_usize), |_, left, right| {
+        let (left, right) = (left?, right?);
+        (left.abs_diff(right) <= 1).then_some(1 + left.max(right))
+    });
+    balanced_height.is_some()
+}
+
+// A tree built by `mirrored`. Its nodes are out of BST order, so it is
+// not a BinarySearchTree, but it frees them without recursion like one
+#[derive(Debug)]
+pub struct Mirror<T> {
+    root: Link<T>,
+}
+
+impl<T> Mirror<T> {
+    pub fn root(&self) -> &Link<T> {
+        &self.root
+    }
+}
+
+impl<T> Drop for Mirror<T> {
+    fn drop(&mut self) {
+        drop_subtree(self.root.take());
+    }
+}
+
+// Copy of the tree with left and right swapped everywhere; the copy is
+// sorted in descending order, so it is no longer a valid BST
+pub fn mirrored<T: Clone>(link: &Link<T>) -> Mirror<T> {
+    let mut mirror = Mirror { root: None };
+    // (node to copy, the empty link its copy goes into)
+    let mut stack: Vec<(&TreeNode<T>, &mut Link<T>)> = Vec::new();
+    if let Some(node) = link {
+        stack.push((node, &mut mirror.root));
+    }
+    while let Some((node, slot)) = stack.pop() {
+        let copy = slot.insert(Box::new(TreeNode {
+            value: node.value.clone(),
+            duplicates: node.duplicates.clone(),
+            size: node.size,
+            left: None,
+            right: None,
+        }));
+        if let Some(right) = &node.right {
+            stack.push((right, &mut copy.left));
+        }
+        if let Some(left) = &node.left {
+            stack.push((left, &mut copy.right));
+        }
+    }
+    mirror
+}
+
+// Same shape and equal values at every position
+pub fn same_tree<T: PartialEq>(a: &Link<T>, b: &Link<T>) -> bool {
+    let mut stack = vec![(a, b)];
+    while let Some(pair) = stack.pop() {
+        match pair {
+            (None, None) => {}
+            (Some(a), Some(b)) => {
+                if a.value != b.value || a.duplicates != b.duplicates {
+                    return false;
+                }
+                stack.push((&a.right, &b.right));
+                stack.push((&a.left, &b.left));
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+// Does some node of `tree` root a subtree identical to `candidate`?
+// Subtree sizes rule out most nodes without comparing them
+pub fn contains_subtree<T: PartialEq>(tree: &Link<T>, candidate: &Link<T>) -> bool {
+    // An empty candidate matches the empty links below every leaf
+    if candidate.is_none() {
+        return true;
+    }
+    let mut stack = vec![tree];
+    while let Some(link) = stack.pop() {
+        let Some(node) = link else {
+            continue;
+        };
+        if node.size == size(candidate) && same_tree(link, candidate) {
+            return true;
+        }
+        stack.push(&node.right);
+        stack.push(&node.left);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BinarySearchTree, DuplicatePolicy};
+    use super::*;
+
+    //          50
+    //      30      70
+    //    20  40  60  80
+    //   10 25 35   65
+    fn equipment() -> BinarySearchTree<i32> {
+        [50, 30, 70, 20, 40, 60, 80, 10, 25, 35, 65, 40]
+            .into_iter()
+            .collect()
+    }
+
+    fn load(text: &str) -> BinarySearchTree<i32> {
+        BinarySearchTree::deserialize(text, DuplicatePolicy::Reject).unwrap()
+    }
+
+    #[test]
+    fn shape_measurements() {
+        let tree = equipment();
+        let root = tree.root();
+        assert_eq!((height(root), diameter(root)), (4, 6));
+        assert_eq!(nodes_at_level(root, 0), [&50]);
+        assert_eq!(nodes_at_level(root, 2), [&20, &40, &60, &80]);
+        assert_eq!(nodes_at_level(root, 3), [&10, &25, &35, &65]);
+        assert!(nodes_at_level(root, 4).is_empty());
+        let empty: Link<i32> = None;
+        assert_eq!((height(&empty), diameter(&empty)), (0, 0));
+
+        // The longest path can skip the root: 10-20-30-40-45-48 is 5 edges,
+        // while the longest path through 50 has only 4
+        let lopsided = load("50,30,20,10,#,#,#,40,#,45,#,48,#,#,#");
+        assert_eq!((height(lopsided.root()), diameter(lopsided.root())), (5, 5));
+    }
+
+    #[test]
+    fn lowest_common_ancestors() {
+        let tree = equipment();
+        let lca = |a, b| lowest_common_ancestor(tree.root(), &a, &b).copied();
+        assert_eq!((lca(10, 35), lca(65, 80)), (Some(30), Some(70)));
+        assert_eq!((lca(20, 10), lca(10, 80)), (Some(20), Some(50)));
+        assert_eq!(lca(25, 25), Some(25));
+        assert_eq!((lca(10, 99), lca(99, 10)), (None, None));
+
+        let temps: BinarySearchTree<i32> =
+            [255, 232, 281, 225, 268, 240, 295, 247, 262].into_iter().collect();
+        assert_eq!(lowest_common_ancestor(temps.root(), &247, &225), Some(&232));
+        assert_eq!(lowest_common_ancestor(temps.root(), &225, &295), Some(&255));
+    }
+
+    #[test]
+    fn paths_and_sums() {
+        let tree = equipment();
+        let paths = root_to_leaf_paths(tree.root());
+        assert_eq!(
+            paths,
+            [
+                vec![&50, &30, &20, &10],
+                vec![&50, &30, &20, &25],
+                vec![&50, &30, &40, &35],
+                vec![&50, &70, &60, &65],
+                vec![&50, &70, &80],
+            ]
+        );
+        assert_eq!(path_sums(tree.root()), [110, 125, 155, 245, 200]);
+        assert!(path_sums::<i32>(&None).is_empty());
+    }
+
+    #[test]
+    fn structural_checks() {
+        let tree = equipment();
+        assert!(is_valid_bst(tree.root()) && is_balanced(tree.root()));
+
+        let chain: BinarySearchTree<i32> = (1..=5).collect();
+        assert!(is_valid_bst(chain.root()) && !is_balanced(chain.root()));
+        assert_eq!(height(chain.root()), 5);
+
+        let mut readings = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+        readings.extend([255, 232, 255, 240, 255, 232]);
+        assert!(is_valid_bst(readings.root()));
+    }
+
+    #[test]
+    fn mirroring() {
+        let tree = equipment();
+        let root = tree.root();
+        let mirror = mirrored(root);
+        let copy = mirror.root();
+        assert!(!is_valid_bst(copy) && !same_tree(copy, root));
+        assert!(same_tree(mirrored(copy).root(), root));
+        assert_eq!(nodes_at_level(copy, 2), [&80, &60, &40, &20]);
+        assert_eq!((height(copy), diameter(copy)), (height(root), diameter(root)));
+        assert!(mirrored::<i32>(&None).root().is_none());
+    }
+
+    #[test]
+    fn subtree_matching() {
+        let tree = equipment();
+        let root = tree.root();
+        let branch = load("30,20,10,#,#,25,#,#,40,35,#,#,#");
+        let pruned = load("30,20,#,#,40,#,#");
+        let leaf = load("65,#,#");
+        assert!(contains_subtree(root, branch.root()));
+        assert!(contains_subtree(root, leaf.root()));
+        assert!(!contains_subtree(root, pruned.root()));
+        assert!(contains_subtree(root, &None));
+        assert!(same_tree(root, equipment().root()));
+    }
+
+    // One stack frame per level would overflow the test thread's stack
+    #[test]
+    fn degenerate_chains_without_recursion() {
+        const DEPTH: i32 = 100_000;
+        // Pre-order of a chain leaning right: every left link is empty
+        let text: Vec<String> = (0..DEPTH).map(|value| format!("{},#", value)).collect();
+        let chain = load(&(text.join(",") + ",#"));
+        let root = chain.root();
+        let depth = DEPTH as usize;
+        assert_eq!((height(root), diameter(root)), (depth, depth - 1));
+        assert_eq!(nodes_at_level(root, depth - 1), [&(DEPTH - 1)]);
+        let paths = root_to_leaf_paths(root);
+        assert_eq!((paths.len(), paths[0].len()), (1, depth));
+        assert!(is_valid_bst(root) && !is_balanced(root));
+        assert!(same_tree(root, load(&(text.join(",") + ",#")).root()));
+        assert!(contains_subtree(root, load(&format!("{},#,#", DEPTH - 1)).root()));
+        assert!(!contains_subtree(root, load("0,#,#").root()));
+
+        let mirror = mirrored(root);
+        assert_eq!(height(mirror.root()), depth);
+        assert!(!is_valid_bst(mirror.root()) && !same_tree(mirror.root(), root));
+        assert_eq!(nodes_at_level(mirror.root(), 1), [&1]);
+        assert!(same_tree(mirrored(mirror.root()).root(), root));
+    }
+}
@@ -0,0 +1,393 @@
+// AVL TREE (Self-Balancing Binary Search Tree)
+// Every node stores its height
+// After each insert/delete the tree rotates so that the heights of the
+// left and right subtrees of any node differ by at most 1
+// Guarantees O(log n) search, insert and delete, even for sorted input
+// Rotations and rebalancing are generic over Balanced, so augmented
+// AVL trees (see interval.rs) reuse them
+
+use std::cmp::{max, Ordering};
+use std::fmt::Display;
+
+use super::render::{Render, RenderNode};
+
+type Link<T> = Option<Box<AvlNode<T>>>;
+
+#[derive(Debug)]
+pub struct AvlNode<T> {
+    value: T,
+    height: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+#[derive(Debug)]
+pub struct AvlTree<T> {
+    root: Link<T>,
+    len: usize,
+}
+
+// What the shared AVL machinery needs from a node. Augmented trees (the
+// interval tree) keep extra subtree data up to date through `refresh`,
+// which runs whenever a node's children have changed
+pub(super) trait Balanced: Sized {
+    fn left(&self) -> &Option<Box<Self>>;
+    fn right(&self) -> &Option<Box<Self>>;
+    fn left_mut(&mut self) -> &mut Option<Box<Self>>;
+    fn right_mut(&mut self) -> &mut Option<Box<Self>>;
+    fn height(&self) -> usize;
+    fn set_height(&mut self, height: usize);
+
+    fn refresh(&mut self) {}
+}
+
+pub(super) fn height<N: Balanced>(link: &Option<Box<N>>) -> usize {
+    link.as_ref().map_or(0, |node| node.height())
+}
+
+fn update<N: Balanced>(node: &mut N) {
+    node.set_height(1 + max(height(node.left()), height(node.right())));
+    node.refresh();
+}
+
+// Positive when the left side is taller, negative when the right side is
+fn balance_factor<N: Balanced>(node: &N) -> isize {
+    height(node.left()) as isize - height(node.right()) as isize
+}
+
+fn rotate_right<N: Balanced>(mut node: Box<N>) -> Box<N> {
+    let mut new_root = node.left_mut().take().expect("rotate_right needs a left child");
+    *node.left_mut() = new_root.right_mut().take();
+    update(&mut *node);
+    *new_root.right_mut() = Some(node);
+    update(&mut *new_root);
+    new_root
+}
+
+fn rotate_left<N: Balanced>(mut node: Box<N>) -> Box<N> {
+    let mut new_root = node.right_mut().take().expect("rotate_left needs a right child");
+    *node.right_mut() = new_root.left_mut().take();
+    update(&mut *node);
+    *new_root.left_mut() = Some(node);
+    update(&mut *new_root);
+    new_root
+}
+
+fn rebalance<N: Balanced>(mut node: Box<N>) -> Box<N> {
+    update(&mut *node);
+    let balance = balance_factor(&*node);
+
+    if balance > 1 {
+        // Left-Right case: straighten the left child first
+        if node
+            .left()
+            .as_ref()
+            .is_some_and(|left| balance_factor(&**left) < 0)
+        {
+            let left = node.left_mut().take();
+            *node.left_mut() = left.map(rotate_left);
+        }
+        return rotate_right(node);
+    }
+    if balance < -1 {
+        // Right-Left case: straighten the right child first
+        if node
+            .right()
+            .as_ref()
+            .is_some_and(|right| balance_factor(&**right) > 0)
+        {
+            let right = node.right_mut().take();
+            *node.right_mut() = right.map(rotate_right);
+        }
+        return rotate_left(node);
+    }
+    node
+}
+
+pub(super) fn rebalance_link<N: Balanced>(link: &mut Option<Box<N>>) {
+    if let Some(node) = link.take() {
+        *link = Some(rebalance(node));
+    }
+}
+
+// Detaches the smallest node of a non-empty subtree, rebalancing on the way up
+fn take_min<N: Balanced>(link: &mut Option<Box<N>>) -> Box<N> {
+    let node = link.as_mut().expect("take_min needs a non-empty subtree");
+    if node.left().is_some() {
+        let min = take_min(node.left_mut());
+        rebalance_link(link);
+        min
+    } else {
+        let mut min = link.take().unwrap();
+        *link = min.right_mut().take();
+        min
+    }
+}
+
+// The subtree that takes a removed node's place: its only child, or with
+// two children the in-order successor. The caller rebalances the link
+pub(super) fn replacement<N: Balanced>(node: &mut N) -> Option<Box<N>> {
+    match (node.left_mut().take(), node.right_mut().take()) {
+        (None, child) | (child, None) => child,
+        (left, mut right) => {
+            let mut successor = take_min(&mut right);
+            *successor.left_mut() = left;
+            *successor.right_mut() = right;
+            Some(successor)
+        }
+    }
+}
+
+impl<T> AvlNode<T> {
+    fn new(value: T) -> Self {
+        AvlNode {
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+impl<T> Balanced for AvlNode<T> {
+    fn left(&self) -> &Link<T> {
+        &self.left
+    }
+
+    fn right(&self) -> &Link<T> {
+        &self.right
+    }
+
+    fn left_mut(&mut self) -> &mut Link<T> {
+        &mut self.left
+    }
+
+    fn right_mut(&mut self) -> &mut Link<T> {
+        &mut self.right
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn set_height(&mut self, height: usize) {
+        self.height = height;
+    }
+}
+
+fn insert_into<T: Ord>(link: &mut Link<T>, value: T) -> bool {
+    let inserted = match link {
+        None => {
+            *link = Some(Box::new(AvlNode::new(value)));
+            return true;
+        }
+        Some(node) => match value.cmp(&node.value) {
+            Ordering::Less => insert_into(&mut node.left, value),
+            Ordering::Greater => insert_into(&mut node.right, value),
+            Ordering::Equal => false, // Value already exists
+        },
+    };
+    if inserted {
+        rebalance_link(link);
+    }
+    inserted
+}
+
+fn remove_from<T: Ord>(link: &mut Link<T>, value: &T) -> Option<T> {
+    let node = link.as_mut()?;
+    let removed = match value.cmp(&node.value) {
+        Ordering::Less => remove_from(&mut node.left, value),
+        Ordering::Greater => remove_from(&mut node.right, value),
+        Ordering::Equal => {
+            let mut node = link.take().unwrap();
+            *link = replacement(&mut *node);
+            Some(node.value)
+        }
+    };
+    if removed.is_some() {
+        rebalance_link(link);
+    }
+    removed
+}
+
+impl<T: Ord> AvlTree<T> {
+    pub fn new() -> Self {
+        AvlTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let inserted = insert_into(&mut self.root, value);
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let removed = remove_from(&mut self.root, value);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match value.cmp(&node.value) {
+                Ordering::Equal => return true,
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+            };
+        }
+        false
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<&T> {
+        fn walk<'a, T>(link: &'a Link<T>, result: &mut Vec<&'a T>) {
+            if let Some(node) = link {
+                walk(&node.left, result);
+                result.push(&node.value);
+                walk(&node.right, result);
+            }
+        }
+        let mut result = Vec::with_capacity(self.len);
+        walk(&self.root, &mut result);
+        result
+    }
+
+    // Debug invariant checker: verifies BST ordering, stored heights,
+    // the AVL balance condition and the element count
+    pub fn check_invariants(&self) -> Result<(), String> {
+        fn check<'a, T: Ord>(
+            link: &'a Link<T>,
+            lower: Option<&'a T>,
+            upper: Option<&'a T>,
+        ) -> Result<(usize, usize), String> {
+            let node = match link {
+                None => return Ok((0, 0)),
+                Some(node) => node,
+            };
+            if lower.is_some_and(|lower| node.value <= *lower)
+                || upper.is_some_and(|upper| node.value >= *upper)
+            {
+                return Err("BST ordering violated".to_string());
+            }
+            let (left_height, left_count) = check(&node.left, lower, Some(&node.value))?;
+            let (right_height, right_count) = check(&node.right, Some(&node.value), upper)?;
+            let expected_height = 1 + max(left_height, right_height);
+            if node.height != expected_height {
+                return Err(format!(
+                    "stored height {} but actual height is {}",
+                    node.height, expected_height
+                ));
+            }
+            if left_height.abs_diff(right_height) > 1 {
+                return Err(format!(
+                    "unbalanced node: left height {}, right height {}",
+                    left_height, right_height
+                ));
+            }
+            Ok((expected_height, left_count + right_count + 1))
+        }
+
+        let (_, count) = check(&self.root, None, None)?;
+        if count != self.len {
+            return Err(format!(
+                "len is {} but tree holds {} nodes",
+                self.len, count
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Display> RenderNode for AvlNode<T> {
+    fn label(&self) -> String {
+        self.value.to_string()
+    }
+
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+}
+
+impl<T: Display> Render for AvlTree<T> {
+    type Node = AvlNode<T>;
+
+    fn render_root(&self) -> Option<&Self::Node> {
+        self.root.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    // xorshift64: repeatable pseudo-random keys without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn sorted_inserts_stay_balanced() {
+        let mut tree = AvlTree::new();
+        for id in 0..1000 {
+            assert!(tree.insert(id));
+        }
+        assert_eq!(tree.check_invariants(), Ok(()));
+        // 1.44 log2(n) bound for AVL trees
+        assert!(tree.height() <= 14, "height {}", tree.height());
+        assert!(!tree.insert(500));
+        assert_eq!(tree.len(), 1000);
+    }
+
+    #[test]
+    fn random_inserts_and_removes_keep_invariants() {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        let mut tree = AvlTree::new();
+        let mut model = BTreeSet::new();
+        for step in 0..4000 {
+            let key = next(&mut state) % 300;
+            if next(&mut state).is_multiple_of(3) {
+                assert_eq!(tree.remove(&key), model.take(&key), "remove {}", key);
+            } else {
+                assert_eq!(tree.insert(key), model.insert(key), "insert {}", key);
+            }
+            if let Err(err) = tree.check_invariants() {
+                panic!("step {}: {}", step, err);
+            }
+            assert_eq!(tree.len(), model.len());
+        }
+        assert_eq!(tree.in_order_traversal(), model.iter().collect::<Vec<_>>());
+        for key in 0..300 {
+            assert_eq!(tree.contains(&key), model.contains(&key));
+        }
+    }
+
+    #[test]
+    fn removing_everything_empties_the_tree() {
+        let mut tree = AvlTree::new();
+        for id in [50, 30, 70, 20, 40, 60, 80] {
+            tree.insert(id);
+        }
+        for id in [50, 20, 80, 30, 70, 40, 60] {
+            assert_eq!(tree.remove(&id), Some(id));
+            assert_eq!(tree.check_invariants(), Ok(()));
+        }
+        assert_eq!((tree.len(), tree.height()), (0, 0));
+        assert_eq!(tree.remove(&50), None);
+    }
+}
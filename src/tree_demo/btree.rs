@@ -0,0 +1,713 @@
+// B-TREE (Multi-way Balanced Search Tree)
+// Each node holds a sorted run of keys and one more child than keys
+// ORDER is the maximum number of children per node:
+// - every node has at most ORDER - 1 keys
+// - every node except the root has at least ceil(ORDER / 2) - 1 keys
+// - all leaves sit at the same depth
+// Wide nodes mean few pointer hops and contiguous keys (cache friendly)
+// Use cases: databases, file systems, large sorted registries
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+use super::render::{Render, RenderNode};
+
+#[derive(Debug)]
+pub struct BNode<T> {
+    keys: Vec<T>,
+    // Empty for leaves, keys.len() + 1 entries otherwise
+    children: Vec<BNode<T>>,
+}
+
+#[derive(Debug)]
+pub struct BTree<T, const ORDER: usize> {
+    root: Option<BNode<T>>,
+    len: usize,
+}
+
+impl<T> BNode<T> {
+    fn leaf(keys: Vec<T>) -> Self {
+        BNode {
+            keys,
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+// Result of inserting into a subtree
+enum Insertion<T> {
+    Done(bool),
+    // The child overflowed: its median moves up, followed by the new right half
+    Split(T, BNode<T>),
+}
+
+impl<T: Ord, const ORDER: usize> BTree<T, ORDER> {
+    const MAX_KEYS: usize = ORDER - 1;
+    const MIN_KEYS: usize = ORDER.div_ceil(2) - 1;
+    const VALID_ORDER: () = assert!(ORDER >= 3, "a B-tree needs ORDER >= 3");
+
+    pub fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_ORDER;
+        BTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Number of levels, counting the leaves
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut current = self.root.as_ref();
+        while let Some(node) = current {
+            height += 1;
+            current = node.children.first();
+        }
+        height
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = self.root.as_ref();
+        while let Some(node) = current {
+            match node.keys.binary_search(value) {
+                Ok(_) => return true,
+                Err(i) => current = node.children.get(i),
+            }
+        }
+        false
+    }
+
+    // Returns true if the value was not already in the tree
+    pub fn insert(&mut self, value: T) -> bool {
+        let root = match &mut self.root {
+            Some(root) => root,
+            None => {
+                self.root = Some(BNode::leaf(vec![value]));
+                self.len += 1;
+                return true;
+            }
+        };
+        let inserted = match Self::insert_into(root, value) {
+            Insertion::Done(inserted) => inserted,
+            Insertion::Split(median, right) => {
+                // The root split: the tree grows one level taller
+                let left = self.root.take().unwrap();
+                self.root = Some(BNode {
+                    keys: vec![median],
+                    children: vec![left, right],
+                });
+                true
+            }
+        };
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+
+    fn insert_into(node: &mut BNode<T>, value: T) -> Insertion<T> {
+        let i = match node.keys.binary_search(&value) {
+            Ok(_) => return Insertion::Done(false), // Value already exists
+            Err(i) => i,
+        };
+        if node.is_leaf() {
+            node.keys.insert(i, value);
+        } else {
+            match Self::insert_into(&mut node.children[i], value) {
+                Insertion::Split(median, right) => {
+                    node.keys.insert(i, median);
+                    node.children.insert(i + 1, right);
+                }
+                done => return done,
+            }
+        }
+
+        if node.keys.len() <= Self::MAX_KEYS {
+            return Insertion::Done(true);
+        }
+        // Overflow: split around the median key
+        let mid = node.keys.len() / 2;
+        let right_keys = node.keys.split_off(mid + 1);
+        let median = node.keys.pop().unwrap();
+        let right_children = if node.is_leaf() {
+            Vec::new()
+        } else {
+            node.children.split_off(mid + 1)
+        };
+        let right = BNode {
+            keys: right_keys,
+            children: right_children,
+        };
+        Insertion::Split(median, right)
+    }
+
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let root = self.root.as_mut()?;
+        let removed = Self::remove_from(root, value)?;
+        self.len -= 1;
+        // An emptied root hands over to its only child (or the tree empties)
+        if root.keys.is_empty() {
+            self.root = root.children.pop();
+        }
+        Some(removed)
+    }
+
+    fn remove_from(node: &mut BNode<T>, value: &T) -> Option<T> {
+        match node.keys.binary_search(value) {
+            Ok(i) if node.is_leaf() => Some(node.keys.remove(i)),
+            Ok(i) => {
+                // Internal key: swap in the in-order predecessor from the left child
+                let predecessor = Self::remove_max(&mut node.children[i]);
+                let removed = mem::replace(&mut node.keys[i], predecessor);
+                Self::fix_underflow(node, i);
+                Some(removed)
+            }
+            Err(_) if node.is_leaf() => None,
+            Err(i) => {
+                let removed = Self::remove_from(&mut node.children[i], value)?;
+                Self::fix_underflow(node, i);
+                Some(removed)
+            }
+        }
+    }
+
+    fn remove_max(node: &mut BNode<T>) -> T {
+        if node.is_leaf() {
+            return node.keys.pop().expect("B-tree nodes are never empty");
+        }
+        let last = node.children.len() - 1;
+        let max = Self::remove_max(&mut node.children[last]);
+        Self::fix_underflow(node, last);
+        max
+    }
+
+    // Tops up child `i` if it dropped below the minimum, by borrowing from
+    // a sibling that can spare a key or by merging with one
+    fn fix_underflow(parent: &mut BNode<T>, i: usize) {
+        if parent.children[i].keys.len() >= Self::MIN_KEYS {
+            return;
+        }
+        let can_lend = |sibling: &BNode<T>| sibling.keys.len() > Self::MIN_KEYS;
+        if i > 0 && can_lend(&parent.children[i - 1]) {
+            borrow_from_left(parent, i);
+        } else if i + 1 < parent.children.len() && can_lend(&parent.children[i + 1]) {
+            borrow_from_right(parent, i);
+        } else if i > 0 {
+            merge_with_right_sibling(parent, i - 1);
+        } else {
+            merge_with_right_sibling(parent, i);
+        }
+    }
+
+    // Bulk-load from strictly ascending input in O(n), packing nodes
+    // level by level instead of inserting one key at a time
+    pub fn from_sorted<I: IntoIterator<Item = T>>(values: I) -> Result<Self, String> {
+        let values: Vec<T> = values.into_iter().collect();
+        if let Some(i) = values.windows(2).position(|pair| pair[0] >= pair[1]) {
+            return Err(format!(
+                "input is not strictly ascending at index {}",
+                i + 1
+            ));
+        }
+        let mut tree = BTree::new();
+        tree.len = values.len();
+        if values.is_empty() {
+            return Ok(tree);
+        }
+
+        // Leaves: each leaf plus the separator after it uses at most ORDER keys
+        let leaf_count = (values.len() + 1).div_ceil(ORDER);
+        let leaf_sizes = even_split(values.len() - (leaf_count - 1), leaf_count);
+        let mut values = values.into_iter();
+        let mut level = Vec::with_capacity(leaf_count);
+        let mut separators = Vec::with_capacity(leaf_count - 1);
+        for (j, size) in leaf_sizes.enumerate() {
+            level.push(BNode::leaf(values.by_ref().take(size).collect()));
+            if j + 1 < leaf_count {
+                separators.extend(values.next());
+            }
+        }
+
+        // Internal levels: group the children under as few parents as possible
+        while level.len() > 1 {
+            let parent_count = level.len().div_ceil(ORDER);
+            let mut children = level.into_iter();
+            let mut keys = separators.into_iter();
+            level = Vec::with_capacity(parent_count);
+            separators = Vec::with_capacity(parent_count - 1);
+            for (j, size) in even_split(children.len(), parent_count).enumerate() {
+                level.push(BNode {
+                    keys: keys.by_ref().take(size - 1).collect(),
+                    children: children.by_ref().take(size).collect(),
+                });
+                if j + 1 < parent_count {
+                    separators.extend(keys.next());
+                }
+            }
+        }
+        tree.root = level.pop();
+        Ok(tree)
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        node.keys.first()
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+        node.keys.last()
+    }
+
+    // In-order (sorted) iterator
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            remaining: self.len,
+        };
+        if let Some(root) = &self.root {
+            push_leftmost(&mut iter.front, root);
+            push_rightmost(&mut iter.back, root);
+        }
+        iter
+    }
+
+    // Lazily yields the values inside `range` in sorted order
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        // Position on the first key inside the lower bound
+        let mut stack = Vec::new();
+        let mut current = self.root.as_ref();
+        while let Some(node) = current {
+            let i = node.keys.partition_point(|key| match range.start_bound() {
+                Bound::Included(start) => key < start,
+                Bound::Excluded(start) => key <= start,
+                Bound::Unbounded => false,
+            });
+            stack.push((node, i));
+            current = node.children.get(i);
+        }
+
+        // Last key inside the upper bound; iteration stops after it
+        let mut last = None;
+        let mut current = self.root.as_ref();
+        while let Some(node) = current {
+            let i = node.keys.partition_point(|key| match range.end_bound() {
+                Bound::Included(end) => key <= end,
+                Bound::Excluded(end) => key < end,
+                Bound::Unbounded => true,
+            });
+            if i > 0 {
+                last = Some(&node.keys[i - 1]);
+            }
+            current = node.children.get(i);
+        }
+
+        Range { stack, last }
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+
+    // Debug invariant checker: key counts, sorted keys within the
+    // separator bounds, child counts, equal leaf depth and the length
+    pub fn check_invariants(&self) -> Result<(), String> {
+        fn check<'a, T: Ord>(
+            node: &'a BNode<T>,
+            lower: Option<&'a T>,
+            upper: Option<&'a T>,
+            is_root: bool,
+            limits: (usize, usize),
+        ) -> Result<(usize, usize), String> {
+            let (min_keys, max_keys) = limits;
+            if node.keys.len() > max_keys || (!is_root && node.keys.len() < min_keys) {
+                return Err(format!("node holds {} keys", node.keys.len()));
+            }
+            if node.keys.is_empty() {
+                return Err("empty node".to_string());
+            }
+            if node.keys.windows(2).any(|pair| pair[0] >= pair[1])
+                || lower.is_some_and(|lower| node.keys[0] <= *lower)
+                || upper.is_some_and(|upper| node.keys[node.keys.len() - 1] >= *upper)
+            {
+                return Err("key ordering violated".to_string());
+            }
+            if node.is_leaf() {
+                return Ok((1, node.keys.len()));
+            }
+            if node.children.len() != node.keys.len() + 1 {
+                return Err(format!(
+                    "{} keys but {} children",
+                    node.keys.len(),
+                    node.children.len()
+                ));
+            }
+
+            let mut depth = None;
+            let mut count = node.keys.len();
+            for (i, child) in node.children.iter().enumerate() {
+                let child_lower = if i == 0 { lower } else { node.keys.get(i - 1) };
+                let child_upper = node.keys.get(i).or(upper);
+                let (child_depth, child_count) =
+                    check(child, child_lower, child_upper, false, limits)?;
+                if depth.is_some_and(|depth| depth != child_depth) {
+                    return Err("leaves at different depths".to_string());
+                }
+                depth = Some(child_depth);
+                count += child_count;
+            }
+            Ok((depth.unwrap() + 1, count))
+        }
+
+        let count = match &self.root {
+            None => 0,
+            Some(root) => {
+                let limits = (Self::MIN_KEYS, Self::MAX_KEYS);
+                check(root, None, None, true, limits)?.1
+            }
+        };
+        if count != self.len {
+            return Err(format!("len is {} but tree holds {} keys", self.len, count));
+        }
+        Ok(())
+    }
+}
+
+// Splits `total` into `parts` sizes that differ by at most one
+fn even_split(total: usize, parts: usize) -> impl Iterator<Item = usize> {
+    let (base, extra) = (total / parts, total % parts);
+    (0..parts).map(move |j| base + usize::from(j < extra))
+}
+
+// Rotates the last key of child i-1 up through the separator into child i
+fn borrow_from_left<T>(parent: &mut BNode<T>, i: usize) {
+    let (left_part, right_part) = parent.children.split_at_mut(i);
+    let left = &mut left_part[i - 1];
+    let child = &mut right_part[0];
+
+    let key = left.keys.pop().unwrap();
+    let separator = mem::replace(&mut parent.keys[i - 1], key);
+    child.keys.insert(0, separator);
+    if let Some(grandchild) = left.children.pop() {
+        child.children.insert(0, grandchild);
+    }
+}
+
+// Rotates the first key of child i+1 up through the separator into child i
+fn borrow_from_right<T>(parent: &mut BNode<T>, i: usize) {
+    let (left_part, right_part) = parent.children.split_at_mut(i + 1);
+    let child = &mut left_part[i];
+    let right = &mut right_part[0];
+
+    let key = right.keys.remove(0);
+    let separator = mem::replace(&mut parent.keys[i], key);
+    child.keys.push(separator);
+    if !right.is_leaf() {
+        child.children.push(right.children.remove(0));
+    }
+}
+
+// Folds child i+1 and the separator between them into child i
+fn merge_with_right_sibling<T>(parent: &mut BNode<T>, i: usize) {
+    let separator = parent.keys.remove(i);
+    let right = parent.children.remove(i + 1);
+    let child = &mut parent.children[i];
+    child.keys.push(separator);
+    child.keys.extend(right.keys);
+    child.children.extend(right.children);
+}
+
+impl<T: Ord, const ORDER: usize> FromIterator<T> for BTree<T, ORDER> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = BTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord, const ORDER: usize> Extend<T> for BTree<T, ORDER> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, T: Ord, const ORDER: usize> IntoIterator for &'a BTree<T, ORDER> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Stack entries are (node, position). Going forwards, position is the
+// next key to yield once children[position] is done; going backwards it
+// is the number of keys still to yield from the right
+type Cursor<'a, T> = Vec<(&'a BNode<T>, usize)>;
+
+fn push_leftmost<'a, T>(stack: &mut Cursor<'a, T>, mut node: &'a BNode<T>) {
+    loop {
+        stack.push((node, 0));
+        match node.children.first() {
+            Some(child) => node = child,
+            None => break,
+        }
+    }
+}
+
+fn push_rightmost<'a, T>(stack: &mut Cursor<'a, T>, mut node: &'a BNode<T>) {
+    loop {
+        stack.push((node, node.keys.len()));
+        match node.children.last() {
+            Some(child) => node = child,
+            None => break,
+        }
+    }
+}
+
+fn step_forward<'a, T>(stack: &mut Cursor<'a, T>) -> Option<&'a T> {
+    loop {
+        let (node, position) = stack.last_mut()?;
+        let node: &'a BNode<T> = node;
+        if *position < node.keys.len() {
+            let key = &node.keys[*position];
+            *position += 1;
+            if let Some(child) = node.children.get(*position) {
+                push_leftmost(stack, child);
+            }
+            return Some(key);
+        }
+        stack.pop();
+    }
+}
+
+fn step_backward<'a, T>(stack: &mut Cursor<'a, T>) -> Option<&'a T> {
+    loop {
+        let (node, position) = stack.last_mut()?;
+        let node: &'a BNode<T> = node;
+        if *position > 0 {
+            *position -= 1;
+            let key = &node.keys[*position];
+            if let Some(child) = node.children.get(*position) {
+                push_rightmost(stack, child);
+            }
+            return Some(key);
+        }
+        stack.pop();
+    }
+}
+
+// In-order iterator walking inwards from both ends
+pub struct Iter<'a, T> {
+    front: Cursor<'a, T>,
+    back: Cursor<'a, T>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        step_forward(&mut self.front)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        step_backward(&mut self.back)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+// Iterator returned by BTree::range
+pub struct Range<'a, T> {
+    stack: Cursor<'a, T>,
+    last: Option<&'a T>,
+}
+
+impl<'a, T: Ord> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let last = self.last?;
+        let key = step_forward(&mut self.stack)?;
+        match key.cmp(last) {
+            Ordering::Greater => {
+                self.last = None;
+                None
+            }
+            Ordering::Equal => {
+                self.last = None;
+                Some(key)
+            }
+            Ordering::Less => Some(key),
+        }
+    }
+}
+
+impl<T: Display> RenderNode for BNode<T> {
+    const BINARY: bool = false;
+
+    fn label(&self) -> String {
+        let keys: Vec<String> = self.keys.iter().map(|key| key.to_string()).collect();
+        keys.join(" | ")
+    }
+
+    fn children(&self) -> Vec<Option<&Self>> {
+        self.children.iter().map(Some).collect()
+    }
+}
+
+impl<T: Display, const ORDER: usize> Render for BTree<T, ORDER> {
+    type Node = BNode<T>;
+
+    fn render_root(&self) -> Option<&Self::Node> {
+        self.root.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random keys without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // Random inserts and removals, with every split, borrow and merge
+    // checked against the standard library's set
+    fn matches_model<const ORDER: usize>(seed: u64) {
+        let mut state = seed;
+        let mut tree = BTree::<u64, ORDER>::new();
+        let mut model = BTreeSet::new();
+        for step in 0..4000 {
+            let key = next(&mut state) % 500;
+            // Insert-heavy first so the tree grows, then removal-heavy
+            let insert = next(&mut state) % 10 < if step < 2000 { 7 } else { 3 };
+            if insert {
+                assert_eq!(tree.insert(key), model.insert(key));
+            } else {
+                assert_eq!(tree.remove(&key), model.take(&key));
+            }
+            assert_eq!(tree.len(), model.len());
+            tree.check_invariants().unwrap();
+        }
+        assert!(tree.iter().eq(model.iter()));
+        assert!(tree.iter().rev().eq(model.iter().rev()));
+        assert_eq!((tree.min(), tree.max()), (model.first(), model.last()));
+        for key in 0..500 {
+            assert_eq!(tree.contains(&key), model.contains(&key));
+        }
+        for _ in 0..200 {
+            let (a, b) = (next(&mut state) % 520, next(&mut state) % 520);
+            let (low, high) = (a.min(b), a.max(b));
+            assert!(tree.range(low..high).eq(model.range(low..high)));
+            assert!(tree.range(low..=high).eq(model.range(low..=high)));
+            assert!(tree.range(low..).eq(model.range(low..)));
+            assert!(tree.range(..high).eq(model.range(..high)));
+        }
+    }
+
+    #[test]
+    fn random_edits_match_a_btreeset() {
+        matches_model::<3>(0x2545_F491_4F6C_DD1D);
+        matches_model::<4>(0x9E37_79B9_7F4A_7C15);
+        matches_model::<5>(0xD1B5_4A32_D192_ED03);
+    }
+
+    #[test]
+    fn inserts_split_and_removals_merge() {
+        let mut tree = BTree::<u32, 3>::new();
+        tree.extend([10, 20]);
+        assert_eq!(tree.height(), 1);
+        // A third key overflows the root leaf, which splits around 20
+        assert!(tree.insert(30));
+        assert_eq!(tree.height(), 2);
+        assert!(!tree.insert(30));
+        assert_eq!(tree.len(), 3);
+
+        // Removing a leaf key that has no spare sibling merges the root away
+        assert_eq!(tree.remove(&10), Some(10));
+        assert_eq!(tree.height(), 1);
+        tree.check_invariants().unwrap();
+        assert_eq!(tree.remove(&10), None);
+        assert_eq!(tree.in_order_traversal(), [&20, &30]);
+
+        // A sibling with a spare key lends it through the separator
+        let mut tree: BTree<u32, 3> = [10, 20, 30, 40].into_iter().collect();
+        assert_eq!(tree.remove(&10), Some(10));
+        tree.check_invariants().unwrap();
+        assert_eq!(tree.height(), 2);
+        assert_eq!(tree.in_order_traversal(), [&20, &30, &40]);
+    }
+
+    #[test]
+    fn from_sorted_packs_every_size() {
+        for len in 0..300u32 {
+            let tree = BTree::<u32, 4>::from_sorted(0..len).unwrap();
+            tree.check_invariants().unwrap();
+            assert_eq!(tree.len(), len as usize);
+            assert!(tree.iter().copied().eq(0..len));
+            // Packed nodes need no more levels than inserting one by one
+            let inserted: BTree<u32, 4> = (0..len).collect();
+            assert!(tree.height() <= inserted.height());
+        }
+        assert_eq!(
+            BTree::<u32, 4>::from_sorted([1, 2, 2]).unwrap_err(),
+            "input is not strictly ascending at index 2"
+        );
+    }
+
+    #[test]
+    fn iter_meets_in_the_middle() {
+        let tree: BTree<u32, 3> = (1..=9).collect();
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), 9);
+        assert_eq!((iter.next(), iter.next_back()), (Some(&1), Some(&9)));
+        assert_eq!((iter.next(), iter.next_back()), (Some(&2), Some(&8)));
+        assert_eq!(iter.len(), 5);
+        let middle: Vec<&u32> = iter.by_ref().collect();
+        assert_eq!(middle, [&3, &4, &5, &6, &7]);
+        assert_eq!((iter.next(), iter.next_back()), (None, None));
+        assert_eq!(BTree::<u32, 3>::new().iter().next_back(), None);
+    }
+}
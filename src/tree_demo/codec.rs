@@ -0,0 +1,510 @@
+// TREE SERIALIZATION (Pre-order with Null Markers)
+// A BST is written as its pre-order walk, with '#' for every empty link:
+//   50,30,#,#,70,#,#
+// Null markers pin down the exact shape, so reading the text back
+// rebuilds the same tree rather than just the same values
+// Copies held by one node (DuplicatePolicy::Keep) are joined with '|'
+// Tokens are trimmed when read; a '\' in front keeps a value's own ',',
+// '|' or '\', a value spelled '#', or a space at either end of a value
+// Also: the classic rebuilds from (pre-order, in-order) and
+// (post-order, in-order) pairs of traversals
+// Every walk uses an explicit stack, so degenerate trees of any depth work
+// Use cases: saving trees to disk, test fixtures, reproducing bug reports
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::iter::drop_subtree;
+use super::ordered::OrderedNode;
+use super::{size, BinarySearchTree, DuplicatePolicy, Link, TreeNode};
+
+const EMPTY: &str = "#";
+const ESCAPE: char = '\\';
+
+// Escapes everything the reader would otherwise take as structure
+fn escape(value: &str) -> String {
+    if value == EMPTY {
+        return format!("{}{}", ESCAPE, EMPTY);
+    }
+    // Whitespace outside this range would be trimmed away
+    let start = value.len() - value.trim_start().len();
+    let end = value.trim_end().len();
+    let mut escaped = String::with_capacity(value.len());
+    for (i, ch) in value.char_indices() {
+        let edge_space = ch.is_whitespace() && (i < start || i >= end);
+        if edge_space || matches!(ch, ESCAPE | ',' | '|') {
+            escaped.push(ESCAPE);
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+fn unescape(part: &str) -> Result<String, String> {
+    let mut value = String::with_capacity(part.len());
+    let mut chars = part.chars();
+    while let Some(ch) = chars.next() {
+        if ch == ESCAPE {
+            let escaped = chars
+                .next()
+                .ok_or_else(|| format!("value '{}' ends in a lone '{}'", part, ESCAPE))?;
+            value.push(escaped);
+        } else {
+            value.push(ch);
+        }
+    }
+    Ok(value)
+}
+
+// Splits on every `separator` that is not escaped
+fn split_unescaped(text: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut rest = Some(text);
+    std::iter::from_fn(move || {
+        let current = rest?;
+        let mut escaped = false;
+        for (i, ch) in current.char_indices() {
+            if escaped {
+                escaped = false;
+            } else if ch == ESCAPE {
+                escaped = true;
+            } else if ch == separator {
+                rest = Some(&current[i + ch.len_utf8()..]);
+                return Some(&current[..i]);
+            }
+        }
+        rest = None;
+        Some(current)
+    })
+}
+
+// Trims whitespace from both ends, keeping an escaped one at the end
+fn trim_unescaped(token: &str) -> &str {
+    let token = token.trim_start();
+    let trimmed = token.trim_end();
+    let escapes = trimmed.chars().rev().take_while(|&ch| ch == ESCAPE).count();
+    if escapes % 2 == 0 {
+        return trimmed;
+    }
+    let kept = token[trimmed.len()..].chars().next().map_or(0, char::len_utf8);
+    &token[..trimmed.len() + kept]
+}
+
+// Wraps already-built subtrees in a new node, summing the subtree sizes
+fn join<T>(value: T, duplicates: Vec<T>, left: Link<T>, right: Link<T>) -> Link<T> {
+    Some(Box::new(TreeNode {
+        size: 1 + duplicates.len() + size(&left) + size(&right),
+        value,
+        duplicates,
+        left,
+        right,
+    }))
+}
+
+// Pre-order walk with an explicit stack, so a degenerate tree cannot
+// overflow the call stack
+fn write_tree<T: Display>(root: &Link<T>, tokens: &mut Vec<String>) {
+    let mut stack = vec![root];
+    while let Some(link) = stack.pop() {
+        match link {
+            None => tokens.push(EMPTY.to_string()),
+            Some(node) => {
+                let copies: Vec<String> =
+                    node.values().map(|value| escape(&value.to_string())).collect();
+                tokens.push(copies.join("|"));
+                stack.push(&node.right);
+                stack.push(&node.left);
+            }
+        }
+    }
+}
+
+// A node whose value has been read but whose subtrees are still coming.
+// Bounds are the stack indices of the ancestors that constrain its value
+struct Pending<T> {
+    value: T,
+    duplicates: Vec<T>,
+    lower: Option<usize>,
+    upper: Option<usize>,
+    // None until the left subtree has been read
+    left: Option<Link<T>>,
+}
+
+fn parse_copies<T: FromStr + PartialEq>(
+    token: &str,
+    policy: DuplicatePolicy,
+) -> Result<(T, Vec<T>), String> {
+    let mut copies = split_unescaped(token, '|').map(|part| {
+        unescape(trim_unescaped(part))?
+            .parse::<T>()
+            .map_err(|_| format!("cannot parse value '{}'", part))
+    });
+    let value = copies.next().expect("split yields at least one part")?;
+    let duplicates = copies.collect::<Result<Vec<T>, String>>()?;
+    if duplicates.iter().any(|copy| *copy != value) {
+        return Err(format!("node '{}' holds copies of different values", token));
+    }
+    if !duplicates.is_empty() && policy == DuplicatePolicy::Reject {
+        return Err(format!(
+            "node '{}' holds duplicates but the policy rejects them",
+            token
+        ));
+    }
+    Ok((value, duplicates))
+}
+
+// Reads one tree, checking that every value fits between the bounds its
+// ancestors impose. Unfinished nodes wait on an explicit stack
+fn read_tree<'a, T: Ord + FromStr>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+    policy: DuplicatePolicy,
+) -> Result<Link<T>, String> {
+    let mut stack: Vec<Pending<T>> = Vec::new();
+    let result = read_into(&mut stack, tokens, policy);
+    // On error, free the partly built subtrees without recursion
+    for pending in stack {
+        drop_subtree(pending.left.flatten());
+    }
+    result
+}
+
+fn read_into<'a, T: Ord + FromStr>(
+    stack: &mut Vec<Pending<T>>,
+    tokens: &mut impl Iterator<Item = &'a str>,
+    policy: DuplicatePolicy,
+) -> Result<Link<T>, String> {
+    loop {
+        // Bounds of the subtree about to be read: the left or right
+        // subtree of the node on top of the stack
+        let (lower, upper) = match stack.last() {
+            None => (None, None),
+            Some(parent) if parent.left.is_none() => (parent.lower, Some(stack.len() - 1)),
+            Some(parent) => (Some(stack.len() - 1), parent.upper),
+        };
+        let token = tokens
+            .next()
+            .ok_or("input ended in the middle of the tree")?;
+        if token != EMPTY {
+            let (value, duplicates) = parse_copies::<T>(token, policy)?;
+            if lower.is_some_and(|lower| value <= stack[lower].value)
+                || upper.is_some_and(|upper| value >= stack[upper].value)
+            {
+                return Err(format!("value '{}' breaks the BST ordering", token));
+            }
+            stack.push(Pending {
+                value,
+                duplicates,
+                lower,
+                upper,
+                left: None,
+            });
+            continue;
+        }
+
+        // A subtree is complete: hang it on its parent, closing every
+        // node whose right subtree this finishes
+        let mut done = None;
+        loop {
+            match stack.last_mut() {
+                None => return Ok(done),
+                Some(parent) if parent.left.is_none() => {
+                    parent.left = Some(done);
+                    break;
+                }
+                Some(_) => {
+                    let node = stack.pop().expect("stack is not empty");
+                    let left = node.left.expect("left subtree was read first");
+                    done = join(node.value, node.duplicates, left, done);
+                }
+            }
+        }
+    }
+}
+
+// Both rebuilds rely on the in-order walk of a BST being sorted: each
+// value's in-order position is looked up once, and the positions alone
+// then fix the shape of the tree
+fn check_traversals<T: Ord>(order: &[T], in_order: &[T], name: &str) -> Result<(), String> {
+    if order.len() != in_order.len() {
+        return Err(format!(
+            "{} has {} values but in-order has {}",
+            name,
+            order.len(),
+            in_order.len()
+        ));
+    }
+    if in_order.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err("in-order is not strictly ascending".to_string());
+    }
+    Ok(())
+}
+
+// In-order position of every value in `order`, each used exactly once
+fn positions<'a, T: Ord + 'a>(
+    order: impl Iterator<Item = &'a T>,
+    in_order: &[T],
+) -> Result<Vec<usize>, String> {
+    let mut seen = vec![false; in_order.len()];
+    order
+        .map(|value| match in_order.binary_search(value) {
+            Ok(position) if !seen[position] => {
+                seen[position] = true;
+                Ok(position)
+            }
+            _ => Err("traversals do not hold the same values".to_string()),
+        })
+        .collect()
+}
+
+// (left, right) child indices per node of a walk
+type Children = Vec<(Option<usize>, Option<usize>)>;
+
+// Links the BST whose pre-order visits these in-order positions, in one
+// stack pass. `lower` is the position of the last node whose right
+// subtree the walk has entered; nothing after it may be smaller
+fn link_pre_order(positions: &[usize], name: &str) -> Result<Children, String> {
+    let mut children = vec![(None, None); positions.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut lower = None;
+    for (index, &position) in positions.iter().enumerate() {
+        if lower.is_some_and(|lower| position < lower) {
+            return Err(format!("{} does not match any BST with these values", name));
+        }
+        let mut parent = None;
+        while let Some(&top) = stack.last() {
+            if positions[top] > position {
+                break;
+            }
+            parent = stack.pop();
+        }
+        match parent {
+            Some(parent) => {
+                lower = Some(positions[parent]);
+                children[parent].1 = Some(index);
+            }
+            None => {
+                if let Some(&top) = stack.last() {
+                    children[top].0 = Some(index);
+                }
+            }
+        }
+        stack.push(index);
+    }
+    Ok(children)
+}
+
+// Boxes the nodes bottom-up: children always come later in the walk
+// than their parent, so walking it backwards finishes them first
+fn assemble<T: Clone>(values: &[&T], children: &Children) -> Link<T> {
+    let mut built: Vec<Link<T>> = (0..values.len()).map(|_| None).collect();
+    for index in (0..values.len()).rev() {
+        let (left, right) = children[index];
+        let left = left.and_then(|child| built[child].take());
+        let right = right.and_then(|child| built[child].take());
+        built[index] = join(values[index].clone(), Vec::new(), left, right);
+    }
+    built.into_iter().next().flatten()
+}
+
+fn build_pre_in<T: Ord + Clone>(pre_order: &[T], in_order: &[T]) -> Result<Link<T>, String> {
+    let positions = positions(pre_order.iter(), in_order)?;
+    let children = link_pre_order(&positions, "pre-order")?;
+    let values: Vec<&T> = pre_order.iter().collect();
+    Ok(assemble(&values, &children))
+}
+
+// Reversed post-order is the pre-order of the mirrored tree (node, right,
+// left), so mirror the positions, link, then swap the children back
+fn build_post_in<T: Ord + Clone>(post_order: &[T], in_order: &[T]) -> Result<Link<T>, String> {
+    let last = in_order.len().saturating_sub(1);
+    let mirrored: Vec<usize> = positions(post_order.iter().rev(), in_order)?
+        .into_iter()
+        .map(|position| last - position)
+        .collect();
+    let children = link_pre_order(&mirrored, "post-order")?
+        .into_iter()
+        .map(|(left, right)| (right, left))
+        .collect();
+    let values: Vec<&T> = post_order.iter().rev().collect();
+    Ok(assemble(&values, &children))
+}
+
+impl<T: Ord> BinarySearchTree<T> {
+    fn with_root(root: Link<T>, policy: DuplicatePolicy) -> Self {
+        BinarySearchTree {
+            len: size(&root),
+            root,
+            policy,
+        }
+    }
+
+    pub fn serialize(&self) -> String
+    where
+        T: Display,
+    {
+        let mut tokens = Vec::with_capacity(2 * self.len + 1);
+        write_tree(&self.root, &mut tokens);
+        tokens.join(",")
+    }
+
+    // Inverse of serialize; rejects malformed text instead of panicking
+    pub fn deserialize(text: &str, policy: DuplicatePolicy) -> Result<Self, String>
+    where
+        T: FromStr,
+    {
+        let mut tokens = split_unescaped(text, ',').map(trim_unescaped);
+        let root = read_tree(&mut tokens, policy)?;
+        // Built before the trailing check so that its Drop frees the nodes
+        let tree = BinarySearchTree::with_root(root, policy);
+        if tokens.next().is_some() {
+            return Err("unexpected values after the end of the tree".to_string());
+        }
+        Ok(tree)
+    }
+
+    pub fn from_pre_and_in_order(pre_order: &[T], in_order: &[T]) -> Result<Self, String>
+    where
+        T: Clone,
+    {
+        check_traversals(pre_order, in_order, "pre-order")?;
+        let root = build_pre_in(pre_order, in_order)?;
+        Ok(BinarySearchTree::with_root(root, DuplicatePolicy::Reject))
+    }
+
+    pub fn from_post_and_in_order(post_order: &[T], in_order: &[T]) -> Result<Self, String>
+    where
+        T: Clone,
+    {
+        check_traversals(post_order, in_order, "post-order")?;
+        let root = build_post_in(post_order, in_order)?;
+        Ok(BinarySearchTree::with_root(root, DuplicatePolicy::Reject))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::ordered::OrderedTree;
+
+    // Deep enough to overflow the call stack with one frame per level
+    const DEPTH: u32 = 200_000;
+
+    // Right spine 0 → 1 → ... written directly as text
+    fn spine_text(depth: u32) -> String {
+        let mut tokens: Vec<String> = Vec::new();
+        for value in 0..depth {
+            tokens.push(value.to_string());
+            tokens.push(EMPTY.to_string());
+        }
+        tokens.push(EMPTY.to_string());
+        tokens.join(",")
+    }
+
+    #[test]
+    fn degenerate_tree_round_trips() {
+        let text = spine_text(DEPTH);
+        let tree = BinarySearchTree::<u32>::deserialize(&text, DuplicatePolicy::Reject).unwrap();
+        assert_eq!(tree.len(), DEPTH as usize);
+        assert_eq!(tree.serialize(), text);
+    }
+
+    #[test]
+    fn degenerate_traversals_rebuild() {
+        let ascending: Vec<u32> = (0..DEPTH).collect();
+        let right_spine = BinarySearchTree::from_pre_and_in_order(&ascending, &ascending).unwrap();
+        assert_eq!(right_spine.serialize(), spine_text(DEPTH));
+        let left_spine = BinarySearchTree::from_post_and_in_order(&ascending, &ascending).unwrap();
+        assert_eq!(left_spine.len(), DEPTH as usize);
+        assert!(left_spine.post_order().eq(ascending.iter()));
+        assert_eq!(left_spine.root().as_ref().unwrap().value, DEPTH - 1);
+    }
+
+    #[test]
+    fn random_shapes_rebuild_from_traversals() {
+        let mut state = 0x2545_F491u32;
+        let tree: BinarySearchTree<u32> = (0..2000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state % 10_000
+            })
+            .collect();
+        let pre_order: Vec<u32> = tree.pre_order().copied().collect();
+        let post_order: Vec<u32> = tree.post_order().copied().collect();
+        let in_order: Vec<u32> = tree.iter().copied().collect();
+        let saved = tree.serialize();
+
+        let from_pre = BinarySearchTree::from_pre_and_in_order(&pre_order, &in_order).unwrap();
+        let from_post = BinarySearchTree::from_post_and_in_order(&post_order, &in_order).unwrap();
+        assert_eq!(from_pre.serialize(), saved);
+        assert_eq!(from_post.serialize(), saved);
+        assert_eq!(from_pre.select(1000), tree.select(1000));
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        let rejects = [
+            "",
+            "50,30,#,#",
+            "50,#,#,70",
+            "50,60,#,#,#",
+            "50,#,40,#,#",
+            "50,30,#,55,#,#,#",
+            "20|20,#,#",
+            "x,#,#",
+        ];
+        for text in rejects {
+            assert!(
+                BinarySearchTree::<i32>::deserialize(text, DuplicatePolicy::Reject).is_err(),
+                "accepted {:?}",
+                text
+            );
+        }
+
+        let build = BinarySearchTree::from_pre_and_in_order;
+        assert!(build(&[2, 3, 1], &[1, 2, 3]).is_err());
+        assert!(build(&[2, 2, 1], &[1, 2, 3]).is_err());
+        assert!(build(&[20, 10, 30], &[10, 20, 40]).is_err());
+        assert!(BinarySearchTree::from_post_and_in_order(&[3, 1, 2], &[1, 2, 3]).is_err());
+        assert!(build(&[], &[] as &[i32]).is_ok_and(|tree| tree.is_empty()));
+    }
+
+    // Values holding separators, the escape, the empty marker or spaces at
+    // their ends come back unchanged, copies included
+    #[test]
+    fn awkward_strings_round_trip() {
+        let values = [
+            "Pump-A, discharge",
+            "A|B",
+            "#",
+            "##",
+            "\\",
+            "C:\\plant\\",
+            "",
+            " ",
+            "  leading",
+            "trailing  ",
+            " both,|# ",
+            "Tank\t",
+        ];
+        let mut tags = BinarySearchTree::with_policy(DuplicatePolicy::Keep);
+        tags.extend(values.map(String::from));
+        tags.extend(["A|B", " both,|# "].map(String::from));
+        let saved = tags.serialize();
+        let restored = BinarySearchTree::<String>::deserialize(&saved, DuplicatePolicy::Keep);
+        let restored = restored.unwrap();
+        assert_eq!(restored.len(), values.len() + 2);
+        assert!(restored.iter().eq(tags.iter()));
+        assert_eq!(restored.serialize(), saved);
+
+        // Hand-written text may still pad its tokens
+        let padded = BinarySearchTree::<String>::deserialize(
+            " b , a\\ ,# , # ,  c|c , #, # ",
+            DuplicatePolicy::Keep,
+        )
+        .unwrap();
+        assert!(padded.iter().eq(["a ", "b", "c", "c"]));
+        let lone = BinarySearchTree::<String>::deserialize("a\\", DuplicatePolicy::Reject);
+        assert_eq!(lone.unwrap_err(), "value 'a\\' ends in a lone '\\'");
+    }
+}
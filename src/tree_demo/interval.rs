@@ -0,0 +1,382 @@
+// INTERVAL TREE (Augmented AVL Tree)
+// Nodes are ordered by interval start (then end), exactly like the AVL
+// tree, and every node also stores the largest end point in its subtree
+// That max_end lets a query skip any subtree that finishes too early
+// Intervals are half-open, [start, end): 14:00–16:00 and 16:00–17:00
+// share an end point but do not overlap
+// Several values may share one interval (one node, many entries)
+// Stabbing and overlap queries run in O(log n + matches)
+// Use cases: scheduling, outage windows, genome ranges, collision checks
+
+use std::cmp::{max, Ordering};
+use std::ops::Bound;
+
+use super::avl::{height, rebalance_link, replacement, Balanced};
+
+type Link<T, V> = Option<Box<IntervalNode<T, V>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Ord> Interval<T> {
+    // Panics on an empty or reversed interval; see try_new
+    pub fn new(start: T, end: T) -> Self {
+        Interval::try_new(start, end).expect("an interval must start before it ends")
+    }
+
+    // None unless start < end
+    pub fn try_new(start: T, end: T) -> Option<Self> {
+        (start < end).then_some(Interval { start, end })
+    }
+
+    pub fn contains(&self, point: &T) -> bool {
+        self.start <= *point && *point < self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+#[derive(Debug)]
+pub struct IntervalNode<T, V> {
+    interval: Interval<T>,
+    values: Vec<V>, // Never empty
+    height: usize,
+    max_end: T,     // Largest end point anywhere in this subtree
+    left: Link<T, V>,
+    right: Link<T, V>,
+}
+
+#[derive(Debug)]
+pub struct IntervalTree<T, V> {
+    root: Link<T, V>,
+    len: usize,
+}
+
+impl<T: Ord + Copy, V> IntervalNode<T, V> {
+    fn new(interval: Interval<T>, value: V) -> Self {
+        IntervalNode {
+            interval,
+            values: vec![value],
+            height: 1,
+            max_end: interval.end,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+// The AVL machinery keeps heights; the hook keeps max_end in step
+impl<T: Ord + Copy, V> Balanced for IntervalNode<T, V> {
+    fn left(&self) -> &Link<T, V> {
+        &self.left
+    }
+
+    fn right(&self) -> &Link<T, V> {
+        &self.right
+    }
+
+    fn left_mut(&mut self) -> &mut Link<T, V> {
+        &mut self.left
+    }
+
+    fn right_mut(&mut self) -> &mut Link<T, V> {
+        &mut self.right
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn set_height(&mut self, height: usize) {
+        self.height = height;
+    }
+
+    fn refresh(&mut self) {
+        self.max_end = [&self.left, &self.right]
+            .into_iter()
+            .flatten()
+            .map(|child| child.max_end)
+            .fold(self.interval.end, max);
+    }
+}
+
+fn insert_into<T: Ord + Copy, V>(link: &mut Link<T, V>, interval: Interval<T>, value: V) {
+    match link {
+        None => {
+            *link = Some(Box::new(IntervalNode::new(interval, value)));
+            return;
+        }
+        Some(node) => match interval.cmp(&node.interval) {
+            Ordering::Less => insert_into(&mut node.left, interval, value),
+            Ordering::Greater => insert_into(&mut node.right, interval, value),
+            // Same window again: keep the entry on the existing node
+            Ordering::Equal => {
+                node.values.push(value);
+                return;
+            }
+        },
+    }
+    rebalance_link(link);
+}
+
+fn remove_from<T: Ord + Copy, V: PartialEq>(
+    link: &mut Link<T, V>,
+    interval: &Interval<T>,
+    value: &V,
+) -> Option<V> {
+    let node = link.as_mut()?;
+    let removed = match interval.cmp(&node.interval) {
+        Ordering::Less => remove_from(&mut node.left, interval, value),
+        Ordering::Greater => remove_from(&mut node.right, interval, value),
+        Ordering::Equal => {
+            let position = node.values.iter().position(|entry| entry == value)?;
+            let removed = node.values.remove(position);
+            if !node.values.is_empty() {
+                // Other entries still use this window; the shape is unchanged
+                return Some(removed);
+            }
+            let mut node = link.take().unwrap();
+            *link = replacement(&mut *node);
+            Some(removed)
+        }
+    };
+    if removed.is_some() {
+        rebalance_link(link);
+    }
+    removed
+}
+
+// Collects entries whose interval ends after `lower` and starts within
+// `upper`, in start order. Subtrees whose max_end is not past `lower`
+// cannot contain a match; neither can anything right of a node that
+// starts too late
+fn search<'a, T: Ord + Copy, V>(
+    link: &'a Link<T, V>,
+    lower: &T,
+    upper: Bound<&T>,
+    out: &mut Vec<(&'a Interval<T>, &'a V)>,
+) {
+    let Some(node) = link else {
+        return;
+    };
+    if node.max_end <= *lower {
+        return;
+    }
+    search(&node.left, lower, upper, out);
+    let starts_in_range = match upper {
+        Bound::Included(upper) => node.interval.start <= *upper,
+        Bound::Excluded(upper) => node.interval.start < *upper,
+        Bound::Unbounded => true,
+    };
+    if !starts_in_range {
+        return;
+    }
+    if node.interval.end > *lower {
+        out.extend(node.values.iter().map(|value| (&node.interval, value)));
+    }
+    search(&node.right, lower, upper, out);
+}
+
+impl<T: Ord + Copy, V> IntervalTree<T, V> {
+    pub fn new() -> Self {
+        IntervalTree { root: None, len: 0 }
+    }
+
+    // Number of entries (an interval holding two values counts twice)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>, value: V) {
+        insert_into(&mut self.root, interval, value);
+        self.len += 1;
+    }
+
+    // Removes one entry with this interval and value
+    pub fn remove(&mut self, interval: &Interval<T>, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        let removed = remove_from(&mut self.root, interval, value);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    // Stabbing query: every entry whose interval contains `point`
+    pub fn stab(&self, point: T) -> Vec<(&Interval<T>, &V)> {
+        let mut out = Vec::new();
+        search(&self.root, &point, Bound::Included(&point), &mut out);
+        out
+    }
+
+    // Every entry whose interval overlaps `query`
+    pub fn overlapping(&self, query: &Interval<T>) -> Vec<(&Interval<T>, &V)> {
+        let mut out = Vec::new();
+        search(&self.root, &query.start, Bound::Excluded(&query.end), &mut out);
+        out
+    }
+
+    // All entries, ordered by interval
+    pub fn entries(&self) -> Vec<(&Interval<T>, &V)> {
+        let mut out = Vec::with_capacity(self.len);
+        fn walk<'a, T, V>(link: &'a Link<T, V>, out: &mut Vec<(&'a Interval<T>, &'a V)>) {
+            if let Some(node) = link {
+                walk(&node.left, out);
+                out.extend(node.values.iter().map(|value| (&node.interval, value)));
+                walk(&node.right, out);
+            }
+        }
+        walk(&self.root, &mut out);
+        out
+    }
+
+    // Debug invariant checker: ordering, heights, AVL balance, the
+    // max_end augmentation and the entry count
+    pub fn check_invariants(&self) -> Result<(), String> {
+        // Returns (height, max_end, entries) of a non-empty subtree
+        fn check<T: Ord + Copy, V>(
+            node: &IntervalNode<T, V>,
+            lower: Option<&Interval<T>>,
+            upper: Option<&Interval<T>>,
+        ) -> Result<(usize, T, usize), String> {
+            if lower.is_some_and(|lower| node.interval <= *lower)
+                || upper.is_some_and(|upper| node.interval >= *upper)
+            {
+                return Err("BST ordering violated".to_string());
+            }
+            if node.values.is_empty() {
+                return Err("node without entries".to_string());
+            }
+            let mut heights = [0, 0];
+            let mut max_end = node.interval.end;
+            let mut count = node.values.len();
+            let children = [
+                (&node.left, lower, Some(&node.interval)),
+                (&node.right, Some(&node.interval), upper),
+            ];
+            for (side, (child, lower, upper)) in children.into_iter().enumerate() {
+                if let Some(child) = child {
+                    let (child_height, child_max, child_count) = check(child, lower, upper)?;
+                    heights[side] = child_height;
+                    max_end = max(max_end, child_max);
+                    count += child_count;
+                }
+            }
+            if node.height != 1 + max(heights[0], heights[1]) {
+                return Err("stored height is stale".to_string());
+            }
+            if heights[0].abs_diff(heights[1]) > 1 {
+                return Err("unbalanced node".to_string());
+            }
+            if node.max_end != max_end {
+                return Err("stored max_end is stale".to_string());
+            }
+            Ok((node.height, max_end, count))
+        }
+
+        let count = match &self.root {
+            None => 0,
+            Some(root) => check(root, None, None)?.2,
+        };
+        if count != self.len {
+            return Err(format!("len is {} but tree holds {} entries", self.len, count));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random windows without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn intervals_are_half_open() {
+        assert_eq!(Interval::try_new(5, 5), None);
+        assert_eq!(Interval::try_new(6, 5), None);
+        let morning = Interval::try_new(480, 600).unwrap();
+        assert!(morning.contains(&480) && morning.contains(&599) && !morning.contains(&600));
+        assert!(!morning.overlaps(&Interval::new(600, 660)));
+        assert!(morning.overlaps(&Interval::new(599, 660)));
+    }
+
+    #[test]
+    #[should_panic(expected = "an interval must start before it ends")]
+    fn new_rejects_reversed_intervals() {
+        Interval::new(10, 1);
+    }
+
+    #[test]
+    fn queries_match_a_linear_scan() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let mut tree = IntervalTree::new();
+        let mut model: Vec<(Interval<u64>, u64)> = Vec::new();
+        for id in 0..3000 {
+            let start = next(&mut state) % 1440;
+            let interval = Interval::new(start, start + 1 + next(&mut state) % 120);
+            if next(&mut state).is_multiple_of(4) && !model.is_empty() {
+                let (interval, id) = model.swap_remove(next(&mut state) as usize % model.len());
+                assert_eq!(tree.remove(&interval, &id), Some(id));
+            } else {
+                tree.insert(interval, id);
+                model.push((interval, id));
+            }
+            if let Err(err) = tree.check_invariants() {
+                panic!("step {}: {}", id, err);
+            }
+        }
+        assert_eq!(tree.len(), model.len());
+
+        let sorted = |mut found: Vec<(Interval<u64>, u64)>| {
+            found.sort();
+            found
+        };
+        for point in (0..1600).step_by(13) {
+            let expected = model.iter().filter(|(interval, _)| interval.contains(&point));
+            let found = tree.stab(point).into_iter().map(|(i, v)| (*i, *v)).collect();
+            assert_eq!(sorted(found), sorted(expected.copied().collect()));
+        }
+        let query = Interval::new(700, 760);
+        let expected = model.iter().filter(|(interval, _)| interval.overlaps(&query));
+        let found = tree.overlapping(&query).into_iter().map(|(i, v)| (*i, *v)).collect();
+        assert_eq!(sorted(found), sorted(expected.copied().collect()));
+    }
+
+    #[test]
+    fn shared_windows_keep_every_entry() {
+        let mut tree = IntervalTree::new();
+        let window = Interval::new(870, 1020);
+        tree.insert(window, "valve test");
+        tree.insert(window, "relief valve");
+        tree.insert(Interval::new(780, 900), "seal swap");
+        assert_eq!((tree.len(), tree.height()), (3, 2));
+        assert_eq!(tree.stab(899).len(), 3);
+
+        assert_eq!(tree.remove(&window, &"valve test"), Some("valve test"));
+        assert_eq!(tree.remove(&window, &"valve test"), None);
+        assert_eq!(tree.stab(1000), [(&window, &"relief valve")]);
+        assert_eq!(tree.check_invariants(), Ok(()));
+    }
+}
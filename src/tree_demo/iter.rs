@@ -0,0 +1,446 @@
+// TREE ITERATORS
+// Lazy traversals of any OrderedNode tree using explicit stacks/queues
+// instead of recursion, so a deep (degenerate) tree cannot overflow the
+// call stack and callers can stop early
+// - In-order:    left, node, right (sorted order)
+// - Pre-order:   node, left, right
+// - Post-order:  left, right, node
+// - Level-order: breadth-first, top to bottom
+// In-order, pre-order and post-order are double-ended: the back end walks
+// the mirrored traversal, and a shared counter stops the ends crossing
+// Each walk visits nodes; a node yields all of its duplicate copies in
+// insertion order
+
+use std::collections::VecDeque;
+use std::iter::{Chain, FlatMap, Once};
+use std::ops::{Bound, RangeBounds};
+use std::slice;
+use std::vec;
+
+use super::ordered::OrderedNode;
+
+// The copies held by one node
+pub type NodeValues<'a, T> = Chain<Once<&'a T>, slice::Iter<'a, T>>;
+
+type ValuesOf<'a, N> = fn(&'a N) -> NodeValues<'a, <N as OrderedNode>::Value>;
+
+fn push_left_spine<'a, N: OrderedNode>(stack: &mut Vec<&'a N>, mut link: Option<&'a N>) {
+    while let Some(node) = link {
+        stack.push(node);
+        link = node.left();
+    }
+}
+
+fn push_right_spine<'a, N: OrderedNode>(stack: &mut Vec<&'a N>, mut link: Option<&'a N>) {
+    while let Some(node) = link {
+        stack.push(node);
+        link = node.right();
+    }
+}
+
+// One step of a pre-order walk (node, left, right), or of
+// (node, right, left) when mirrored
+fn pre_order_step<'a, N: OrderedNode>(stack: &mut Vec<&'a N>, mirrored: bool) -> Option<&'a N> {
+    let node = stack.pop()?;
+    let (first, second) = if mirrored {
+        (node.right(), node.left())
+    } else {
+        (node.left(), node.right())
+    };
+    stack.extend(second);
+    stack.extend(first);
+    Some(node)
+}
+
+// One step of a post-order walk (left, right, node), or of
+// (right, left, node) when mirrored. The flag marks nodes whose
+// children have already been queued
+fn post_order_step<'a, N: OrderedNode>(
+    stack: &mut Vec<(&'a N, bool)>,
+    mirrored: bool,
+) -> Option<&'a N> {
+    loop {
+        let (node, expanded) = stack.pop()?;
+        if expanded {
+            return Some(node);
+        }
+        let (first, second) = if mirrored {
+            (node.right(), node.left())
+        } else {
+            (node.left(), node.right())
+        };
+        stack.push((node, true));
+        stack.extend(second.map(|child| (child, false)));
+        stack.extend(first.map(|child| (child, false)));
+    }
+}
+
+// Flattens a node walk into the values it holds. `remaining` counts
+// values, so the iterator can report an exact length
+pub struct Values<'a, N: OrderedNode, I> {
+    inner: FlatMap<I, NodeValues<'a, N::Value>, ValuesOf<'a, N>>,
+    remaining: usize,
+}
+
+impl<'a, N: OrderedNode + 'a, I: Iterator<Item = &'a N>> Values<'a, N, I> {
+    fn from_nodes(nodes: I, len: usize) -> Self {
+        Values {
+            inner: nodes.flat_map(N::values as ValuesOf<'a, N>),
+            remaining: len,
+        }
+    }
+}
+
+impl<'a, N: OrderedNode + 'a, I: Iterator<Item = &'a N>> Iterator for Values<'a, N, I> {
+    type Item = &'a N::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.inner.next()?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, N: OrderedNode + 'a, I: DoubleEndedIterator<Item = &'a N>> DoubleEndedIterator
+    for Values<'a, N, I>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(value)
+    }
+}
+
+impl<'a, N: OrderedNode + 'a, I: Iterator<Item = &'a N>> ExactSizeIterator
+    for Values<'a, N, I>
+{
+}
+
+pub type Iter<'a, N> = Values<'a, N, InOrderNodes<'a, N>>;
+pub type PreOrder<'a, N> = Values<'a, N, PreOrderNodes<'a, N>>;
+pub type PostOrder<'a, N> = Values<'a, N, PostOrderNodes<'a, N>>;
+pub type LevelOrder<'a, N> = Values<'a, N, LevelOrderNodes<'a, N>>;
+
+// The node walks below share one `remaining` value count between their
+// two ends; a node is handed out by whichever end reaches it first
+
+pub struct InOrderNodes<'a, N> {
+    front: Vec<&'a N>,
+    back: Vec<&'a N>,
+    remaining: usize,
+}
+
+impl<'a, N: OrderedNode> Iter<'a, N> {
+    pub(super) fn new(root: Option<&'a N>, len: usize) -> Self {
+        let mut nodes = InOrderNodes {
+            front: Vec::new(),
+            back: Vec::new(),
+            remaining: len,
+        };
+        push_left_spine(&mut nodes.front, root);
+        push_right_spine(&mut nodes.back, root);
+        Values::from_nodes(nodes, len)
+    }
+}
+
+impl<'a, N: OrderedNode> Iterator for InOrderNodes<'a, N> {
+    type Item = &'a N;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        push_left_spine(&mut self.front, node.right());
+        self.remaining -= node.count();
+        Some(node)
+    }
+}
+
+impl<N: OrderedNode> DoubleEndedIterator for InOrderNodes<'_, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        push_right_spine(&mut self.back, node.left());
+        self.remaining -= node.count();
+        Some(node)
+    }
+}
+
+pub struct PreOrderNodes<'a, N> {
+    front: Vec<&'a N>,
+    // Reverse of pre-order is the mirrored post-order
+    back: Vec<(&'a N, bool)>,
+    remaining: usize,
+}
+
+impl<'a, N: OrderedNode> PreOrder<'a, N> {
+    pub(super) fn new(root: Option<&'a N>, len: usize) -> Self {
+        let nodes = PreOrderNodes {
+            front: root.into_iter().collect(),
+            back: root.map(|node| (node, false)).into_iter().collect(),
+            remaining: len,
+        };
+        Values::from_nodes(nodes, len)
+    }
+}
+
+impl<'a, N: OrderedNode> Iterator for PreOrderNodes<'a, N> {
+    type Item = &'a N;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = pre_order_step(&mut self.front, false)?;
+        self.remaining -= node.count();
+        Some(node)
+    }
+}
+
+impl<N: OrderedNode> DoubleEndedIterator for PreOrderNodes<'_, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = post_order_step(&mut self.back, true)?;
+        self.remaining -= node.count();
+        Some(node)
+    }
+}
+
+pub struct PostOrderNodes<'a, N> {
+    front: Vec<(&'a N, bool)>,
+    // Reverse of post-order is the mirrored pre-order
+    back: Vec<&'a N>,
+    remaining: usize,
+}
+
+impl<'a, N: OrderedNode> PostOrder<'a, N> {
+    pub(super) fn new(root: Option<&'a N>, len: usize) -> Self {
+        let nodes = PostOrderNodes {
+            front: root.map(|node| (node, false)).into_iter().collect(),
+            back: root.into_iter().collect(),
+            remaining: len,
+        };
+        Values::from_nodes(nodes, len)
+    }
+}
+
+impl<'a, N: OrderedNode> Iterator for PostOrderNodes<'a, N> {
+    type Item = &'a N;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = post_order_step(&mut self.front, false)?;
+        self.remaining -= node.count();
+        Some(node)
+    }
+}
+
+impl<N: OrderedNode> DoubleEndedIterator for PostOrderNodes<'_, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = pre_order_step(&mut self.back, true)?;
+        self.remaining -= node.count();
+        Some(node)
+    }
+}
+
+// Breadth-first; not double-ended since the last level is only known at the end
+pub struct LevelOrderNodes<'a, N> {
+    queue: VecDeque<&'a N>,
+}
+
+impl<'a, N: OrderedNode> LevelOrder<'a, N> {
+    pub(super) fn new(root: Option<&'a N>, len: usize) -> Self {
+        let nodes = LevelOrderNodes {
+            queue: root.into_iter().collect(),
+        };
+        Values::from_nodes(nodes, len)
+    }
+}
+
+impl<'a, N: OrderedNode> Iterator for LevelOrderNodes<'a, N> {
+    type Item = &'a N;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.left());
+        self.queue.extend(node.right());
+        Some(node)
+    }
+}
+
+fn above_lower_bound<T: Ord>(value: &T, bound: Bound<&T>) -> bool {
+    match bound {
+        Bound::Included(start) => value >= start,
+        Bound::Excluded(start) => value > start,
+        Bound::Unbounded => true,
+    }
+}
+
+fn below_upper_bound<T: Ord>(value: &T, bound: Bound<&T>) -> bool {
+    match bound {
+        Bound::Included(end) => value <= end,
+        Bound::Excluded(end) => value < end,
+        Bound::Unbounded => true,
+    }
+}
+
+// Iterator returned by OrderedTree::range
+pub struct Range<'a, N: OrderedNode> {
+    inner: FlatMap<RangeNodes<'a, N>, NodeValues<'a, N::Value>, ValuesOf<'a, N>>,
+}
+
+pub struct RangeNodes<'a, N: OrderedNode> {
+    stack: Vec<&'a N>,
+    // Last value inside the upper bound; iteration stops after it
+    last: Option<&'a N::Value>,
+}
+
+impl<'a, N: OrderedNode> Range<'a, N>
+where
+    N::Value: Ord,
+{
+    pub(super) fn new<R: RangeBounds<N::Value>>(root: Option<&'a N>, range: R) -> Self {
+        // Path to the first value inside the lower bound
+        let mut stack = Vec::new();
+        let mut current = root;
+        while let Some(node) = current {
+            if above_lower_bound(node.value(), range.start_bound()) {
+                stack.push(node);
+                current = node.left();
+            } else {
+                current = node.right();
+            }
+        }
+
+        let mut last = None;
+        let mut current = root;
+        while let Some(node) = current {
+            if below_upper_bound(node.value(), range.end_bound()) {
+                last = Some(node.value());
+                current = node.right();
+            } else {
+                current = node.left();
+            }
+        }
+
+        let nodes = RangeNodes { stack, last };
+        Range {
+            inner: nodes.flat_map(N::values as ValuesOf<'a, N>),
+        }
+    }
+}
+
+impl<'a, N: OrderedNode> Iterator for RangeNodes<'a, N>
+where
+    N::Value: Ord,
+{
+    type Item = &'a N;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let last = self.last?;
+        let node = self.stack.pop()?;
+        if node.value() > last {
+            self.stack.clear();
+            return None;
+        }
+
+        // Queue up the in-order successors from the right subtree
+        push_left_spine(&mut self.stack, node.right());
+        Some(node)
+    }
+}
+
+impl<'a, N: OrderedNode> Iterator for Range<'a, N>
+where
+    N::Value: Ord,
+{
+    type Item = &'a N::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+// Consuming in-order iterator. Nodes are detached one at a time as they
+// are visited, so no recursive Box drop ever happens
+pub struct IntoIter<N: OrderedNode> {
+    stack: Vec<Box<N>>,
+    // Remaining copies of the node being visited
+    duplicates: vec::IntoIter<N::Value>,
+    remaining: usize,
+}
+
+impl<N: OrderedNode> IntoIter<N> {
+    pub(super) fn new(root: Option<Box<N>>, len: usize) -> Self {
+        let mut iter = IntoIter {
+            stack: Vec::new(),
+            duplicates: Vec::new().into_iter(),
+            remaining: len,
+        };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut link: Option<Box<N>>) {
+        while let Some(mut node) = link {
+            link = node.take_left();
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<N: OrderedNode> Iterator for IntoIter<N> {
+    type Item = N::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = match self.duplicates.next() {
+            Some(copy) => copy,
+            None => {
+                let mut node = self.stack.pop()?;
+                self.push_left_spine(node.take_right());
+                let (value, duplicates) = node.into_values();
+                self.duplicates = duplicates.into_iter();
+                value
+            }
+        };
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<N: OrderedNode> ExactSizeIterator for IntoIter<N> {}
+
+impl<N: OrderedNode> Drop for IntoIter<N> {
+    fn drop(&mut self) {
+        // Drain the rest so that any remaining subtrees are freed iteratively
+        for _ in self.by_ref() {}
+    }
+}
+
+// Frees a subtree without recursion: children are detached onto an
+// explicit stack before their parent is dropped
+pub(super) fn drop_subtree<N: OrderedNode>(root: Option<Box<N>>) {
+    let mut stack: Vec<Box<N>> = root.into_iter().collect();
+    while let Some(mut node) = stack.pop() {
+        stack.extend(node.take_left());
+        stack.extend(node.take_right());
+    }
+}
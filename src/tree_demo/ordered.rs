@@ -0,0 +1,203 @@
+// ORDERED TREE SURFACE (shared by the binary search trees)
+// Navigation, order statistics and traversals only depend on the shape of
+// a sorted binary tree, so they are written once here
+// Every binary search tree in tree_demo implements OrderedTree by exposing
+// its root; nodes implement OrderedNode by exposing their children, the
+// copies they hold and their subtree size
+// Balancing (or the lack of it) stays in each tree's own module
+
+use std::cmp::Ordering;
+use std::ops::RangeBounds;
+
+use super::iter::{Iter, LevelOrder, NodeValues, PostOrder, PreOrder, Range};
+
+// One node as the shared algorithms see it
+pub trait OrderedNode: Sized {
+    type Value;
+
+    fn value(&self) -> &Self::Value;
+
+    fn left(&self) -> Option<&Self>;
+
+    fn right(&self) -> Option<&Self>;
+
+    // All copies held by this node, in insertion order
+    fn values(&self) -> NodeValues<'_, Self::Value>;
+
+    // How many copies of the value this node holds
+    fn count(&self) -> usize {
+        1
+    }
+
+    // Number of values in the subtree rooted here, duplicates included
+    // (order-statistic augmentation)
+    fn size(&self) -> usize;
+
+    // Detach a child, for the owning iterator and iterative drops
+    fn take_left(&mut self) -> Option<Box<Self>>;
+
+    fn take_right(&mut self) -> Option<Box<Self>>;
+
+    // The node's value followed by any later copies
+    fn into_values(self) -> (Self::Value, Vec<Self::Value>);
+}
+
+fn subtree_size<N: OrderedNode>(node: Option<&N>) -> usize {
+    node.map_or(0, N::size)
+}
+
+pub trait OrderedTree {
+    type Value: Ord;
+    type Node: OrderedNode<Value = Self::Value>;
+
+    fn root_node(&self) -> Option<&Self::Node>;
+
+    // Number of values, duplicates included
+    fn len(&self) -> usize;
+
+    // In-order (sorted) iterator
+    fn iter(&self) -> Iter<'_, Self::Node> {
+        Iter::new(self.root_node(), self.len())
+    }
+
+    fn pre_order(&self) -> PreOrder<'_, Self::Node> {
+        PreOrder::new(self.root_node(), self.len())
+    }
+
+    fn post_order(&self) -> PostOrder<'_, Self::Node> {
+        PostOrder::new(self.root_node(), self.len())
+    }
+
+    fn level_order(&self) -> LevelOrder<'_, Self::Node> {
+        LevelOrder::new(self.root_node(), self.len())
+    }
+
+    fn in_order_traversal(&self) -> Vec<&Self::Value> {
+        self.iter().collect()
+    }
+
+    // Lazily yields the values inside `range` in sorted order
+    fn range<R: RangeBounds<Self::Value>>(&self, range: R) -> Range<'_, Self::Node> {
+        Range::new(self.root_node(), range)
+    }
+
+    fn min(&self) -> Option<&Self::Value> {
+        let mut current = self.root_node()?;
+        while let Some(left) = current.left() {
+            current = left;
+        }
+        Some(current.value())
+    }
+
+    fn max(&self) -> Option<&Self::Value> {
+        let mut current = self.root_node()?;
+        while let Some(right) = current.right() {
+            current = right;
+        }
+        Some(current.value())
+    }
+
+    // Greatest value <= `value`
+    fn floor(&self, value: &Self::Value) -> Option<&Self::Value> {
+        closest_below(self.root_node(), value, true)
+    }
+
+    // Smallest value >= `value`
+    fn ceiling(&self, value: &Self::Value) -> Option<&Self::Value> {
+        closest_above(self.root_node(), value, true)
+    }
+
+    // Greatest value strictly below `value`
+    fn predecessor(&self, value: &Self::Value) -> Option<&Self::Value> {
+        closest_below(self.root_node(), value, false)
+    }
+
+    // Smallest value strictly above `value`
+    fn successor(&self, value: &Self::Value) -> Option<&Self::Value> {
+        closest_above(self.root_node(), value, false)
+    }
+
+    // k-th smallest value (0-based), in O(height) using subtree sizes
+    fn select(&self, k: usize) -> Option<&Self::Value> {
+        let mut k = k;
+        let mut current = self.root_node();
+        while let Some(node) = current {
+            let left_size = subtree_size(node.left());
+            if k < left_size {
+                current = node.left();
+            } else if k < left_size + node.count() {
+                return node.values().nth(k - left_size);
+            } else {
+                k -= left_size + node.count();
+                current = node.right();
+            }
+        }
+        None
+    }
+
+    // Number of values strictly below `value`, in O(height)
+    fn rank(&self, value: &Self::Value) -> usize {
+        let mut rank = 0;
+        let mut current = self.root_node();
+        while let Some(node) = current {
+            match value.cmp(node.value()) {
+                Ordering::Less => current = node.left(),
+                Ordering::Equal => return rank + subtree_size(node.left()),
+                Ordering::Greater => {
+                    rank += subtree_size(node.left()) + node.count();
+                    current = node.right();
+                }
+            }
+        }
+        rank
+    }
+}
+
+fn closest_below<'a, N>(
+    root: Option<&'a N>,
+    value: &N::Value,
+    inclusive: bool,
+) -> Option<&'a N::Value>
+where
+    N: OrderedNode,
+    N::Value: Ord,
+{
+    let mut best = None;
+    let mut current = root;
+    while let Some(node) = current {
+        match node.value().cmp(value) {
+            Ordering::Equal if inclusive => return Some(node.value()),
+            Ordering::Less => {
+                best = Some(node.value());
+                current = node.right();
+            }
+            _ => current = node.left(),
+        }
+    }
+    best
+}
+
+fn closest_above<'a, N>(
+    root: Option<&'a N>,
+    value: &N::Value,
+    inclusive: bool,
+) -> Option<&'a N::Value>
+where
+    N: OrderedNode,
+    N::Value: Ord,
+{
+    let mut best = None;
+    let mut current = root;
+    while let Some(node) = current {
+        match node.value().cmp(value) {
+            Ordering::Equal if inclusive => return Some(node.value()),
+            Ordering::Greater => {
+                best = Some(node.value());
+                current = node.left();
+            }
+            _ => current = node.right(),
+        }
+    }
+    best
+}
+
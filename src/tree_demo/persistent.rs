@@ -0,0 +1,482 @@
+// PERSISTENT TREE (Immutable BST with Structural Sharing)
+// Nodes are never modified after creation; they live behind Rc
+// insert/remove copy only the path from the root to the change and
+// return a new version, sharing every other subtree with the old one
+// Each update costs O(height) new nodes, and old versions stay valid
+// Use cases: snapshots, undo history, cheap diffs between versions
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use super::render::{Render, RenderNode};
+
+type PLink<T> = Option<Rc<PNode<T>>>;
+
+#[derive(Debug)]
+pub struct PNode<T> {
+    value: T,
+    size: usize, // Nodes in this subtree, including itself
+    left: PLink<T>,
+    right: PLink<T>,
+}
+
+#[derive(Debug)]
+pub struct PersistentTree<T> {
+    root: PLink<T>,
+}
+
+// Cloning a version only bumps the root's reference count
+impl<T> Clone for PersistentTree<T> {
+    fn clone(&self) -> Self {
+        PersistentTree {
+            root: self.root.clone(),
+        }
+    }
+}
+
+fn size<T>(link: &PLink<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<T> PNode<T> {
+    fn new(value: T, left: PLink<T>, right: PLink<T>) -> Rc<Self> {
+        Rc::new(PNode {
+            value,
+            size: 1 + size(&left) + size(&right),
+            left,
+            right,
+        })
+    }
+
+    fn min(&self) -> &T {
+        let mut current = self;
+        while let Some(left) = &current.left {
+            current = left;
+        }
+        &current.value
+    }
+}
+
+// Returns the new subtree, or None if the value was already present
+fn insert_into<T: Ord + Clone>(link: &PLink<T>, value: T) -> Option<Rc<PNode<T>>> {
+    let node = match link {
+        None => return Some(PNode::new(value, None, None)),
+        Some(node) => node,
+    };
+    match value.cmp(&node.value) {
+        Ordering::Less => {
+            let left = insert_into(&node.left, value)?;
+            Some(PNode::new(
+                node.value.clone(),
+                Some(left),
+                node.right.clone(),
+            ))
+        }
+        Ordering::Greater => {
+            let right = insert_into(&node.right, value)?;
+            Some(PNode::new(
+                node.value.clone(),
+                node.left.clone(),
+                Some(right),
+            ))
+        }
+        Ordering::Equal => None, // Value already exists
+    }
+}
+
+// Returns the new subtree, or None if the value was not found
+fn remove_from<T: Ord + Clone>(link: &PLink<T>, value: &T) -> Option<PLink<T>> {
+    let node = link.as_ref()?;
+    let new_node = match value.cmp(&node.value) {
+        Ordering::Less => {
+            let left = remove_from(&node.left, value)?;
+            PNode::new(node.value.clone(), left, node.right.clone())
+        }
+        Ordering::Greater => {
+            let right = remove_from(&node.right, value)?;
+            PNode::new(node.value.clone(), node.left.clone(), right)
+        }
+        Ordering::Equal => match (&node.left, &node.right) {
+            (None, child) | (child, None) => return Some(child.clone()),
+            (Some(_), Some(right)) => {
+                // Two children: the in-order successor takes this node's place
+                let (successor, rest) = remove_min(right);
+                PNode::new(successor, node.left.clone(), rest)
+            }
+        },
+    };
+    Some(Some(new_node))
+}
+
+// Copies the left spine down to the minimum; returns it and the new subtree
+fn remove_min<T: Clone>(node: &Rc<PNode<T>>) -> (T, PLink<T>) {
+    match &node.left {
+        None => (node.value.clone(), node.right.clone()),
+        Some(left) => {
+            let (min, rest) = remove_min(left);
+            let new_node = PNode::new(node.value.clone(), rest, node.right.clone());
+            (min, Some(new_node))
+        }
+    }
+}
+
+impl<T: Ord + Clone> PersistentTree<T> {
+    pub fn new() -> Self {
+        PersistentTree { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match value.cmp(&node.value) {
+                Ordering::Equal => return true,
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+            };
+        }
+        false
+    }
+
+    // Returns a new version containing `value`; self is left untouched
+    pub fn insert(&self, value: T) -> Self {
+        match insert_into(&self.root, value) {
+            Some(root) => PersistentTree { root: Some(root) },
+            None => self.clone(),
+        }
+    }
+
+    // Returns a new version without `value`; self is left untouched
+    pub fn remove(&self, value: &T) -> Self {
+        match remove_from(&self.root, value) {
+            Some(root) => PersistentTree { root },
+            None => self.clone(),
+        }
+    }
+
+    // In-order (sorted) iterator
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            remaining: self.len(),
+        };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for PersistentTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(PersistentTree::new(), |tree, value| tree.insert(value))
+    }
+}
+
+impl<'a, T: Ord + Clone> IntoIterator for &'a PersistentTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T> {
+    stack: Vec<&'a PNode<T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut link: &'a PLink<T>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        self.remaining -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+// Values that differ between two versions, both lists in sorted order
+#[derive(Debug)]
+pub struct TreeDiff<'a, T> {
+    pub added: Vec<&'a T>,
+    pub removed: Vec<&'a T>,
+    pub visited: usize, // Nodes that had to be opened up
+    pub shared: usize,  // Nodes skipped because both versions share them
+}
+
+// Work left to do on one side of a diff, in sorted order
+enum Pending<'a, T> {
+    Subtree(&'a Rc<PNode<T>>),
+    Value(&'a T),
+}
+
+impl<T> Clone for Pending<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Pending<'_, T> {}
+
+struct DiffSide<'a, T> {
+    stack: Vec<Pending<'a, T>>,
+}
+
+impl<'a, T> DiffSide<'a, T> {
+    fn new(root: &'a PLink<T>) -> Self {
+        DiffSide {
+            stack: root.iter().map(Pending::Subtree).collect(),
+        }
+    }
+
+    fn peek(&self) -> Option<Pending<'a, T>> {
+        self.stack.last().copied()
+    }
+
+    // Replaces the subtree on top with its left subtree, value and right subtree
+    fn open(&mut self, visited: &mut usize) {
+        if let Some(Pending::Subtree(node)) = self.stack.pop() {
+            *visited += 1;
+            self.stack.extend(node.right.as_ref().map(Pending::Subtree));
+            self.stack.push(Pending::Value(&node.value));
+            self.stack.extend(node.left.as_ref().map(Pending::Subtree));
+        }
+    }
+
+    fn drain_into(&mut self, values: &mut Vec<&'a T>, visited: &mut usize) {
+        while let Some(pending) = self.peek() {
+            match pending {
+                Pending::Value(value) => {
+                    values.push(value);
+                    self.stack.pop();
+                }
+                Pending::Subtree(_) => self.open(visited),
+            }
+        }
+    }
+}
+
+// Sorted merge of both versions that skips any subtree the two share,
+// so only the paths copied by insert/remove are actually walked
+pub fn diff<'a, T: Ord>(old: &'a PersistentTree<T>, new: &'a PersistentTree<T>) -> TreeDiff<'a, T> {
+    let mut result = TreeDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        visited: 0,
+        shared: 0,
+    };
+    let mut old_side = DiffSide::new(&old.root);
+    let mut new_side = DiffSide::new(&new.root);
+
+    loop {
+        match (old_side.peek(), new_side.peek()) {
+            (None, None) => break,
+            (Some(Pending::Subtree(a)), Some(Pending::Subtree(b))) => {
+                if Rc::ptr_eq(a, b) {
+                    result.shared += a.size;
+                    old_side.stack.pop();
+                    new_side.stack.pop();
+                } else {
+                    // Open the bigger side first so the halves line up again
+                    if a.size >= b.size {
+                        old_side.open(&mut result.visited);
+                    }
+                    if b.size >= a.size {
+                        new_side.open(&mut result.visited);
+                    }
+                }
+            }
+            (Some(Pending::Value(a)), Some(Pending::Value(b))) => match a.cmp(b) {
+                Ordering::Less => {
+                    result.removed.push(a);
+                    old_side.stack.pop();
+                }
+                Ordering::Greater => {
+                    result.added.push(b);
+                    new_side.stack.pop();
+                }
+                Ordering::Equal => {
+                    old_side.stack.pop();
+                    new_side.stack.pop();
+                }
+            },
+            // A value sorting before a whole subtree can be settled without
+            // opening that subtree
+            (Some(Pending::Value(a)), Some(Pending::Subtree(b))) if a < b.min() => {
+                result.removed.push(a);
+                old_side.stack.pop();
+            }
+            (Some(Pending::Subtree(a)), Some(Pending::Value(b))) if b < a.min() => {
+                result.added.push(b);
+                new_side.stack.pop();
+            }
+            (Some(Pending::Value(_)), Some(Pending::Subtree(_))) => {
+                new_side.open(&mut result.visited)
+            }
+            (Some(Pending::Subtree(_)), Some(Pending::Value(_))) => {
+                old_side.open(&mut result.visited)
+            }
+            // Whatever is left once the other side runs dry is one-sided
+            (Some(_), None) => old_side.drain_into(&mut result.removed, &mut result.visited),
+            (None, Some(_)) => new_side.drain_into(&mut result.added, &mut result.visited),
+        }
+    }
+    result
+}
+
+impl<T: Display> RenderNode for PNode<T> {
+    fn label(&self) -> String {
+        self.value.to_string()
+    }
+
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+}
+
+impl<T: Display> Render for PersistentTree<T> {
+    type Node = PNode<T>;
+
+    fn render_root(&self) -> Option<&Self::Node> {
+        self.root.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random edits without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    fn child(link: &PLink<u32>, left: bool) -> &Rc<PNode<u32>> {
+        let node = link.as_ref().unwrap();
+        if left { &node.left } else { &node.right }.as_ref().unwrap()
+    }
+
+    //        50
+    //      /    \
+    //    30      70
+    //   /  \    /  \
+    //  20  40  60  80
+    fn shift() -> PersistentTree<u32> {
+        [50, 30, 70, 20, 40, 60, 80].into_iter().collect()
+    }
+
+    #[test]
+    fn edits_leave_older_versions_alone() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let mut versions = vec![(PersistentTree::new(), BTreeSet::new())];
+        for _ in 0..600 {
+            let (tree, model) = versions.last().unwrap();
+            let (mut tree, mut model) = (tree.clone(), model.clone());
+            let value = (next(&mut state) % 100) as u32;
+            if next(&mut state).is_multiple_of(3) {
+                tree = tree.remove(&value);
+                model.remove(&value);
+            } else {
+                tree = tree.insert(value);
+                model.insert(value);
+            }
+            versions.push((tree, model));
+        }
+        for (tree, model) in &versions {
+            assert_eq!(tree.len(), model.len());
+            assert!(tree.iter().eq(model.iter()));
+            assert!((0..100).all(|value| tree.contains(&value) == model.contains(&value)));
+        }
+    }
+
+    #[test]
+    fn untouched_subtrees_are_shared() {
+        let old = shift();
+        // 65 goes 50 → 70 → 60: only that path is copied
+        let new = old.insert(65);
+        assert!(Rc::ptr_eq(child(&old.root, true), child(&new.root, true)));
+        let old_seventy = &old.root.as_ref().unwrap().right;
+        let new_seventy = &new.root.as_ref().unwrap().right;
+        assert!(Rc::ptr_eq(child(old_seventy, false), child(new_seventy, false)));
+        assert!(!Rc::ptr_eq(child(old_seventy, true), child(new_seventy, true)));
+        assert!(!Rc::ptr_eq(old.root.as_ref().unwrap(), new.root.as_ref().unwrap()));
+        assert_eq!(Rc::strong_count(child(&old.root, true)), 2);
+
+        // Removing 30 copies the root and rebuilds the left side only
+        let smaller = new.remove(&30);
+        assert!(Rc::ptr_eq(child(&new.root, false), child(&smaller.root, false)));
+        assert_eq!(Rc::strong_count(child(&new.root, false)), 2);
+        assert_eq!(smaller.in_order_traversal(), [&20, &40, &50, &60, &65, &70, &80]);
+
+        // No-op edits hand back the very same root
+        for same in [old.insert(50), old.remove(&55)] {
+            assert!(Rc::ptr_eq(old.root.as_ref().unwrap(), same.root.as_ref().unwrap()));
+        }
+        drop(new);
+        drop(smaller);
+        assert_eq!(Rc::strong_count(child(&old.root, true)), 1);
+    }
+
+    #[test]
+    fn diff_matches_set_differences() {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        let mut draw = |limit: u64| (next(&mut state) % limit) as u32;
+        let base: PersistentTree<u32> = (0..200).map(|_| draw(400)).collect();
+        for edits in [0, 1, 5, 40] {
+            let mut new = base.clone();
+            for _ in 0..edits {
+                let value = draw(400);
+                new = if draw(2) == 0 { new.insert(value) } else { new.remove(&value) };
+            }
+            let old_set: BTreeSet<&u32> = base.iter().collect();
+            let new_set: BTreeSet<&u32> = new.iter().collect();
+            let changes = diff(&base, &new);
+            assert!(changes.added.iter().eq(new_set.difference(&old_set)));
+            assert!(changes.removed.iter().eq(old_set.difference(&new_set)));
+            let back = diff(&new, &base);
+            assert_eq!((back.added, back.removed), (changes.removed, changes.added));
+        }
+
+        // A single insert only opens the copied path
+        let one = base.insert(1000);
+        let changes = diff(&base, &one);
+        assert_eq!((changes.added, changes.removed.len()), (vec![&1000], 0));
+        assert!(changes.shared > 0 && changes.visited < base.len());
+        let full = shift();
+        assert_eq!(diff(&PersistentTree::new(), &full).added, full.in_order_traversal());
+    }
+}
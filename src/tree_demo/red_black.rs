@@ -0,0 +1,559 @@
+// RED-BLACK TREE (Left-Leaning Red-Black BST)
+// Every node is coloured red or black, with these invariants:
+// - The root is black
+// - A red node never has a red child
+// - Every path from a node down to an empty link crosses the same
+//   number of black nodes (the black height)
+// - Left-leaning variant: red links only ever lean left
+// Height stays below 2*log2(n + 1), with fewer rotations per insert than AVL
+// Use cases: write-heavy ordered indexes, std::map in many languages
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::iter::once;
+use std::mem;
+
+use super::iter::{drop_subtree, IntoIter, Iter, NodeValues};
+use super::ordered::{OrderedNode, OrderedTree};
+use super::render::{Render, RenderNode};
+
+type Link<T> = Option<Box<RbNode<T>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Black,
+}
+
+impl Color {
+    fn flipped(self) -> Color {
+        match self {
+            Color::Red => Color::Black,
+            Color::Black => Color::Red,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RbNode<T> {
+    value: T,
+    color: Color,
+    // Number of nodes in the subtree rooted here (order-statistic
+    // augmentation, kept up to date by rotations and fix_up)
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+#[derive(Debug)]
+pub struct RedBlackTree<T> {
+    root: Link<T>,
+    len: usize,
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn is_red<T>(link: &Link<T>) -> bool {
+    link.as_ref().is_some_and(|node| node.color == Color::Red)
+}
+
+// Is the left child of this link's node red?
+fn is_left_red<T>(link: &Link<T>) -> bool {
+    link.as_ref().is_some_and(|node| is_red(&node.left))
+}
+
+impl<T> RbNode<T> {
+    fn new(value: T) -> Self {
+        // New nodes are always red; fix_up restores the invariants
+        RbNode {
+            value,
+            color: Color::Red,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    fn flip_colors(&mut self) {
+        self.color = self.color.flipped();
+        for child in [&mut self.left, &mut self.right].into_iter().flatten() {
+            child.color = child.color.flipped();
+        }
+    }
+}
+
+fn rotate_left<T>(mut node: Box<RbNode<T>>) -> Box<RbNode<T>> {
+    let mut new_root = node.right.take().expect("rotate_left needs a right child");
+    node.right = new_root.left.take();
+    new_root.color = node.color;
+    node.color = Color::Red;
+    node.update_size();
+    new_root.left = Some(node);
+    new_root.update_size();
+    new_root
+}
+
+fn rotate_right<T>(mut node: Box<RbNode<T>>) -> Box<RbNode<T>> {
+    let mut new_root = node.left.take().expect("rotate_right needs a left child");
+    node.left = new_root.right.take();
+    new_root.color = node.color;
+    node.color = Color::Red;
+    node.update_size();
+    new_root.right = Some(node);
+    new_root.update_size();
+    new_root
+}
+
+// Restores the left-leaning invariants on the way back up
+fn fix_up<T>(mut node: Box<RbNode<T>>) -> Box<RbNode<T>> {
+    if is_red(&node.right) && !is_red(&node.left) {
+        node = rotate_left(node);
+    }
+    if is_red(&node.left) && is_left_red(&node.left) {
+        node = rotate_right(node);
+    }
+    if is_red(&node.left) && is_red(&node.right) {
+        node.flip_colors();
+    }
+    node.update_size();
+    node
+}
+
+// Borrow a red link so the left child is not a lone 2-node
+fn move_red_left<T>(mut node: Box<RbNode<T>>) -> Box<RbNode<T>> {
+    node.flip_colors();
+    if is_left_red(&node.right) {
+        node.right = node.right.take().map(rotate_right);
+        node = rotate_left(node);
+        node.flip_colors();
+    }
+    node
+}
+
+// Borrow a red link so the right child is not a lone 2-node
+fn move_red_right<T>(mut node: Box<RbNode<T>>) -> Box<RbNode<T>> {
+    node.flip_colors();
+    if is_left_red(&node.left) {
+        node = rotate_right(node);
+        node.flip_colors();
+    }
+    node
+}
+
+fn insert_into<T: Ord>(link: Link<T>, value: T, inserted: &mut bool) -> Box<RbNode<T>> {
+    let mut node = match link {
+        None => {
+            *inserted = true;
+            return Box::new(RbNode::new(value));
+        }
+        Some(node) => node,
+    };
+    match value.cmp(&node.value) {
+        Ordering::Less => node.left = Some(insert_into(node.left.take(), value, inserted)),
+        Ordering::Greater => node.right = Some(insert_into(node.right.take(), value, inserted)),
+        Ordering::Equal => {} // Value already exists
+    }
+    fix_up(node)
+}
+
+// Detaches the smallest node; returns the remaining subtree and that node
+fn delete_min<T>(mut node: Box<RbNode<T>>) -> (Link<T>, Box<RbNode<T>>) {
+    if node.left.is_none() {
+        return (node.right.take(), node);
+    }
+    if !is_red(&node.left) && !is_left_red(&node.left) {
+        node = move_red_left(node);
+    }
+    let (left, min) = delete_min(node.left.take().unwrap());
+    node.left = left;
+    (Some(fix_up(node)), min)
+}
+
+// Removes `value`, which the caller has checked is in the subtree
+fn delete<T: Ord>(mut node: Box<RbNode<T>>, value: &T, removed: &mut Option<T>) -> Link<T> {
+    if *value < node.value {
+        if !is_red(&node.left) && !is_left_red(&node.left) {
+            node = move_red_left(node);
+        }
+        node.left = delete(node.left.take().unwrap(), value, removed);
+    } else {
+        if is_red(&node.left) {
+            node = rotate_right(node);
+        }
+        if *value == node.value && node.right.is_none() {
+            *removed = Some(node.value);
+            return None;
+        }
+        if !is_red(&node.right) && !is_left_red(&node.right) {
+            node = move_red_right(node);
+        }
+        if *value == node.value {
+            // Replace with the in-order successor, then delete that instead
+            let (right, successor) = delete_min(node.right.take().unwrap());
+            *removed = Some(mem::replace(&mut node.value, successor.value));
+            node.right = right;
+        } else {
+            node.right = delete(node.right.take().unwrap(), value, removed);
+        }
+    }
+    Some(fix_up(node))
+}
+
+impl<T: Ord> RedBlackTree<T> {
+    pub fn new() -> Self {
+        RedBlackTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        drop_subtree(self.root.take());
+        self.len = 0;
+    }
+
+    pub fn height(&self) -> usize {
+        fn height<T>(link: &Link<T>) -> usize {
+            link.as_ref()
+                .map_or(0, |node| 1 + height(&node.left).max(height(&node.right)))
+        }
+        height(&self.root)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match value.cmp(&node.value) {
+                Ordering::Equal => return true,
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+            };
+        }
+        false
+    }
+
+    // Returns true if the value was not already in the tree
+    pub fn insert(&mut self, value: T) -> bool {
+        let mut inserted = false;
+        let mut root = insert_into(self.root.take(), value, &mut inserted);
+        root.color = Color::Black;
+        self.root = Some(root);
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        if !self.contains(value) {
+            return None;
+        }
+        let mut root = self.root.take()?;
+        if !is_red(&root.left) && !is_red(&root.right) {
+            root.color = Color::Red;
+        }
+        let mut removed = None;
+        self.root = delete(root, value, &mut removed);
+        if let Some(root) = &mut self.root {
+            root.color = Color::Black;
+        }
+        self.len -= 1;
+        removed
+    }
+
+    // Validator for the colour and black-height invariants (plus BST
+    // ordering, subtree sizes and the element count)
+    pub fn check_invariants(&self) -> Result<(), String> {
+        fn check<'a, T: Ord>(
+            link: &'a Link<T>,
+            lower: Option<&'a T>,
+            upper: Option<&'a T>,
+        ) -> Result<(usize, usize), String> {
+            let node = match link {
+                None => return Ok((0, 0)),
+                Some(node) => node,
+            };
+            if lower.is_some_and(|lower| node.value <= *lower)
+                || upper.is_some_and(|upper| node.value >= *upper)
+            {
+                return Err("BST ordering violated".to_string());
+            }
+            if node.color == Color::Red && (is_red(&node.left) || is_red(&node.right)) {
+                return Err("red node has a red child".to_string());
+            }
+            if is_red(&node.right) {
+                return Err("red link leans right".to_string());
+            }
+            let (left_black, left_count) = check(&node.left, lower, Some(&node.value))?;
+            let (right_black, right_count) = check(&node.right, Some(&node.value), upper)?;
+            if left_black != right_black {
+                return Err(format!(
+                    "black heights differ: left {}, right {}",
+                    left_black, right_black
+                ));
+            }
+            let count = left_count + right_count + 1;
+            if node.size != count {
+                return Err(format!(
+                    "stored size {} but subtree holds {} nodes",
+                    node.size, count
+                ));
+            }
+            let own_black = usize::from(node.color == Color::Black);
+            Ok((left_black + own_black, count))
+        }
+
+        if is_red(&self.root) {
+            return Err("root is red".to_string());
+        }
+        let (_, count) = check(&self.root, None, None)?;
+        if count != self.len {
+            return Err(format!(
+                "len is {} but tree holds {} nodes",
+                self.len, count
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Ord> FromIterator<T> for RedBlackTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = RedBlackTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for RedBlackTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> OrderedTree for RedBlackTree<T> {
+    type Value = T;
+    type Node = RbNode<T>;
+
+    fn root_node(&self) -> Option<&RbNode<T>> {
+        self.root.as_deref()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// Dropping the root Box directly would recurse once per level
+impl<T> Drop for RedBlackTree<T> {
+    fn drop(&mut self) {
+        drop_subtree(self.root.take());
+    }
+}
+
+impl<T> IntoIterator for RedBlackTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<RbNode<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter::new(self.root.take(), self.len)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a RedBlackTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, RbNode<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// A set tree: every node holds exactly one value
+impl<T> OrderedNode for RbNode<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+
+    fn values(&self) -> NodeValues<'_, T> {
+        once(&self.value).chain([].iter())
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn take_left(&mut self) -> Link<T> {
+        self.left.take()
+    }
+
+    fn take_right(&mut self) -> Link<T> {
+        self.right.take()
+    }
+
+    fn into_values(self) -> (T, Vec<T>) {
+        (self.value, Vec::new())
+    }
+}
+
+impl<T: Display> RenderNode for RbNode<T> {
+    fn label(&self) -> String {
+        match self.color {
+            Color::Red => format!("{} (red)", self.value),
+            Color::Black => self.value.to_string(),
+        }
+    }
+
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+
+    fn dot_attributes(&self) -> Option<String> {
+        let color = match self.color {
+            Color::Red => "red",
+            Color::Black => "black",
+        };
+        Some(format!(
+            "style=filled, fillcolor={}, fontcolor=white",
+            color
+        ))
+    }
+}
+
+impl<T: Display> Render for RedBlackTree<T> {
+    type Node = RbNode<T>;
+
+    fn render_root(&self) -> Option<&Self::Node> {
+        self.root.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    // xorshift64: repeatable pseudo-random keys without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn sorted_inserts_stay_balanced() {
+        let tree: RedBlackTree<u32> = (0..3000).collect();
+        assert_eq!(tree.check_invariants(), Ok(()));
+        // 2 log2(n + 1) bound for red-black trees
+        assert!(tree.height() <= 23, "height {}", tree.height());
+        assert_eq!(tree.len(), 3000);
+    }
+
+    #[test]
+    fn random_inserts_and_removes_keep_invariants() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let mut tree = RedBlackTree::new();
+        let mut model = BTreeSet::new();
+        for step in 0..4000 {
+            let key = next(&mut state) % 300;
+            if next(&mut state).is_multiple_of(3) {
+                assert_eq!(tree.remove(&key), model.take(&key), "remove {}", key);
+            } else {
+                assert_eq!(tree.insert(key), model.insert(key), "insert {}", key);
+            }
+            if let Err(err) = tree.check_invariants() {
+                panic!("step {}: {}", step, err);
+            }
+            assert_eq!(tree.len(), model.len());
+        }
+        assert_eq!(tree.in_order_traversal(), model.iter().collect::<Vec<_>>());
+        assert!(tree.iter().rev().eq(model.iter().rev()));
+    }
+
+    #[test]
+    fn ordered_queries_match_a_sorted_set() {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        let model: BTreeSet<u64> = (0..500).map(|_| next(&mut state) % 2000).collect();
+        let tree: RedBlackTree<u64> = model.iter().copied().collect();
+        let sorted: Vec<&u64> = model.iter().collect();
+
+        assert_eq!((tree.min(), tree.max()), (model.first(), model.last()));
+        for (k, value) in sorted.iter().enumerate() {
+            assert_eq!(tree.select(k), Some(*value));
+            assert_eq!(tree.rank(value), k);
+        }
+        assert_eq!(tree.select(sorted.len()), None);
+        for probe in (0..2000).step_by(7) {
+            assert_eq!(tree.floor(&probe), model.range(..=probe).next_back());
+            assert_eq!(tree.ceiling(&probe), model.range(probe..).next());
+            assert_eq!(tree.predecessor(&probe), model.range(..probe).next_back());
+            assert_eq!(tree.successor(&probe), model.range(probe + 1..).next());
+            assert_eq!(tree.rank(&probe), model.range(..probe).count());
+        }
+        assert!(tree.range(400..=900).eq(model.range(400..=900)));
+        assert!(tree.range(..100).eq(model.range(..100)));
+    }
+
+    #[test]
+    fn traversals_visit_every_node_once() {
+        let tree: RedBlackTree<i32> = [40, 20, 60, 10, 30, 50, 70].into_iter().collect();
+        assert_eq!(tree.check_invariants(), Ok(()));
+        assert_eq!(
+            tree.pre_order().copied().collect::<Vec<_>>(),
+            [40, 20, 10, 30, 60, 50, 70]
+        );
+        assert_eq!(
+            tree.post_order().copied().collect::<Vec<_>>(),
+            [10, 30, 20, 50, 70, 60, 40]
+        );
+        assert_eq!(
+            tree.level_order().copied().collect::<Vec<_>>(),
+            [40, 20, 60, 10, 30, 50, 70]
+        );
+        let forward: Vec<_> = tree.pre_order().collect();
+        assert!(tree.pre_order().rev().eq(forward.into_iter().rev()));
+    }
+
+    #[test]
+    fn into_iter_drains_in_order_and_clear_empties() {
+        let mut tree: RedBlackTree<u32> = (0..10_000).rev().collect();
+        let drained: Vec<u32> = tree.into_iter().collect();
+        assert!(drained.into_iter().eq(0..10_000));
+
+        tree = (0..10_000).collect();
+        let mut partial = tree.into_iter();
+        assert_eq!(partial.len(), 10_000);
+        assert_eq!(partial.nth(4), Some(4));
+        drop(partial);
+
+        tree = (0..100).collect();
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.check_invariants(), Ok(()));
+        assert!(tree.insert(7));
+    }
+}
@@ -0,0 +1,238 @@
+// TREE RENDERING (ASCII art and Graphviz DOT)
+// Draws the actual shape of a tree rather than its sorted contents
+// Every tree type in tree_demo implements Render by exposing its root;
+// nodes implement RenderNode by listing their child slots
+// Heights are measured in one bottom-up pass before drawing, so stored
+// heights are never trusted
+// Use cases: bug reports, checking that balancing really works
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+// One node as the renderer sees it
+pub trait RenderNode {
+    // Binary nodes get L/R slot names and a balance factor
+    const BINARY: bool = true;
+
+    fn label(&self) -> String;
+
+    // Child slots from left to right; binary nodes always return
+    // [left, right] so an empty side still shows up
+    fn children(&self) -> Vec<Option<&Self>>;
+
+    // Extra Graphviz attributes, e.g. the colour of a red-black node
+    fn dot_attributes(&self) -> Option<String> {
+        None
+    }
+}
+
+pub trait Render {
+    type Node: RenderNode;
+
+    fn render_root(&self) -> Option<&Self::Node>;
+
+    // Indented drawing, root first. Binary nodes show their height and
+    // balance factor (left height - right height); wider nodes just height
+    fn to_ascii(&self) -> String {
+        let mut out = String::new();
+        match self.render_root() {
+            None => out.push_str("(empty)\n"),
+            Some(root) => draw(root, "", "", &measure(root), &mut out),
+        }
+        out
+    }
+
+    // Graphviz digraph; render with `dot -Tpng tree.dot -o tree.png`
+    fn to_dot(&self, name: &str) -> String {
+        let mut out = format!("digraph {} {{\n", quoted(name));
+        out.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        if let Some(root) = self.render_root() {
+            let mut next_id = 0;
+            write_dot(root, &mut next_id, &measure(root), &mut out);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+// DOT identifier for any string: quoted, with quotes and backslashes escaped
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Subtree height of every node, keyed by node address
+type Heights<N> = HashMap<*const N, usize>;
+
+// Measures all heights bottom-up in one pass, so drawing never walks a
+// subtree more than once
+fn measure<N: RenderNode>(root: &N) -> Heights<N> {
+    fn visit<N: RenderNode>(node: &N, heights: &mut Heights<N>) -> usize {
+        let tallest_child = node
+            .children()
+            .into_iter()
+            .flatten()
+            .map(|child| visit(child, heights))
+            .max()
+            .unwrap_or(0);
+        heights.insert(node, 1 + tallest_child);
+        1 + tallest_child
+    }
+    let mut heights = HashMap::new();
+    visit(root, &mut heights);
+    heights
+}
+
+fn summary<N: RenderNode>(node: &N, heights: &Heights<N>) -> String {
+    let height = |child: Option<&N>| child.map_or(0, |child| heights[&(child as *const N)]);
+    match node.children().as_slice() {
+        [left, right] if N::BINARY => {
+            let left_height = height(*left);
+            let right_height = height(*right);
+            let balance = left_height as isize - right_height as isize;
+            format!("h={}, bf={}", 1 + left_height.max(right_height), balance)
+        }
+        _ => format!("h={}", height(Some(node))),
+    }
+}
+
+fn slot_name<N: RenderNode>(index: usize) -> String {
+    match (N::BINARY, index) {
+        (true, 0) => "L".to_string(),
+        (true, _) => "R".to_string(),
+        (false, _) => index.to_string(),
+    }
+}
+
+fn draw<N: RenderNode>(
+    node: &N,
+    prefix: &str,
+    child_prefix: &str,
+    heights: &Heights<N>,
+    out: &mut String,
+) {
+    let _ = writeln!(out, "{}{} [{}]", prefix, node.label(), summary(node, heights));
+    let children = node.children();
+    // A leaf gets no lines at all; otherwise empty slots are drawn as ·
+    if children.iter().all(Option::is_none) {
+        return;
+    }
+    let slots = children.len();
+    for (index, child) in children.into_iter().enumerate() {
+        let last = index + 1 == slots;
+        let branch = if last { "└── " } else { "├── " };
+        let indent = if last { "    " } else { "│   " };
+        let name = slot_name::<N>(index);
+        match child {
+            Some(child) => {
+                let prefix = format!("{}{}{}: ", child_prefix, branch, name);
+                let child_prefix = format!("{}{}", child_prefix, indent);
+                draw(child, &prefix, &child_prefix, heights, out);
+            }
+            None => {
+                let _ = writeln!(out, "{}{}{}: ·", child_prefix, branch, name);
+            }
+        }
+    }
+}
+
+// Writes the node and its subtree; returns the node's DOT id
+fn write_dot<N: RenderNode>(
+    node: &N,
+    next_id: &mut usize,
+    heights: &Heights<N>,
+    out: &mut String,
+) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    let label = format!("{}\\n{}", node.label(), summary(node, heights)).replace('"', "\\\"");
+    let extra = node
+        .dot_attributes()
+        .map_or(String::new(), |attributes| format!(", {}", attributes));
+    let _ = writeln!(out, "    n{} [label=\"{}\"{}];", id, label, extra);
+
+    let children = node.children();
+    if children.iter().all(Option::is_none) {
+        return id;
+    }
+    for (index, child) in children.into_iter().enumerate() {
+        let name = slot_name::<N>(index);
+        let child_id = match child {
+            Some(child) => write_dot(child, next_id, heights, out),
+            None => {
+                // A point placeholder keeps a lone child on its own side
+                let empty_id = *next_id;
+                *next_id += 1;
+                let _ = writeln!(out, "    n{} [shape=point];", empty_id);
+                empty_id
+            }
+        };
+        let _ = writeln!(out, "    n{} -> n{} [label=\"{}\"];", id, child_id, name);
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Minimal binary node: only its shape matters to the renderer
+    struct Node(u32, Option<Box<Node>>, Option<Box<Node>>);
+
+    impl RenderNode for Node {
+        fn label(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn children(&self) -> Vec<Option<&Self>> {
+            vec![self.1.as_deref(), self.2.as_deref()]
+        }
+    }
+
+    struct Tree(Option<Node>);
+
+    impl Render for Tree {
+        type Node = Node;
+
+        fn render_root(&self) -> Option<&Node> {
+            self.0.as_ref()
+        }
+    }
+
+    fn leaf(value: u32) -> Option<Box<Node>> {
+        Some(Box::new(Node(value, None, None)))
+    }
+
+    #[test]
+    fn ascii_shows_heights_and_balance_factors() {
+        let tree = Tree(Some(Node(2, Some(Box::new(Node(1, leaf(0), None))), leaf(3))));
+        assert_eq!(
+            tree.to_ascii(),
+            "2 [h=3, bf=1]\n\
+             ├── L: 1 [h=2, bf=1]\n\
+             │   ├── L: 0 [h=1, bf=0]\n\
+             │   └── R: ·\n\
+             └── R: 3 [h=1, bf=0]\n"
+        );
+        assert_eq!(Tree(None).to_ascii(), "(empty)\n");
+    }
+
+    #[test]
+    fn right_spine_heights_count_down() {
+        let mut spine = None;
+        for value in (0..200).rev() {
+            spine = Some(Box::new(Node(value, None, spine)));
+        }
+        let tree = Tree(spine.map(|root| *root));
+        let ascii = tree.to_ascii();
+        assert!(ascii.starts_with("0 [h=200, bf=-199]\n"));
+        assert!(ascii.contains("R: 199 [h=1, bf=0]\n"));
+    }
+
+    #[test]
+    fn dot_name_is_quoted_and_escaped() {
+        let tree = Tree(Some(Node(1, None, None)));
+        let dot = tree.to_dot("pump \"A\" \\ backup");
+        assert!(dot.starts_with("digraph \"pump \\\"A\\\" \\\\ backup\" {\n"));
+        assert!(dot.contains("n0 [label=\"1\\nh=1, bf=0\"];"));
+    }
+}
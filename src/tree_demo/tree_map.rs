@@ -0,0 +1,422 @@
+// TREE MAP (Ordered Map on a Binary Search Tree)
+// Same node layout as TreeNode, but each node stores a (key, value) pair
+// Nodes are ordered by key only, so values can be any type
+// Operations: get, get_mut, insert, remove, entry, ordered iteration
+// Use cases: sorted dictionaries, attaching metadata to ordered IDs
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::mem;
+
+use super::render::{Render, RenderNode};
+
+type Link<K, V> = Option<Box<MapNode<K, V>>>;
+
+#[derive(Debug)]
+pub struct MapNode<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+#[derive(Debug)]
+pub struct TreeMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K, V> MapNode<K, V> {
+    fn new(key: K, value: V) -> Self {
+        MapNode {
+            key,
+            value,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+// Walks down to the link that holds `key`, or to the empty link where it
+// would be inserted
+fn find_slot<'a, K: Ord, V>(mut link: &'a mut Link<K, V>, key: &K) -> &'a mut Link<K, V> {
+    loop {
+        match link.as_ref().map(|node| key.cmp(&node.key)) {
+            None | Some(Ordering::Equal) => return link,
+            Some(Ordering::Less) => link = &mut link.as_mut().unwrap().left,
+            Some(Ordering::Greater) => link = &mut link.as_mut().unwrap().right,
+        }
+    }
+}
+
+// Detaches the node holding the smallest key of a non-empty subtree
+fn take_min<K, V>(mut link: &mut Link<K, V>) -> Box<MapNode<K, V>> {
+    while link.as_ref().is_some_and(|node| node.left.is_some()) {
+        link = &mut link.as_mut().unwrap().left;
+    }
+    let mut min = link.take().expect("take_min needs a non-empty subtree");
+    *link = min.right.take();
+    min
+}
+
+// Removes the node at a non-empty link, splicing its children back in
+fn unlink<K, V>(link: &mut Link<K, V>) -> (K, V) {
+    let mut node = link.take().expect("unlink needs a non-empty link");
+    *link = match (node.left.take(), node.right.take()) {
+        (None, child) | (child, None) => child,
+        (left, mut right) => {
+            let mut successor = take_min(&mut right);
+            successor.left = left;
+            successor.right = right;
+            Some(successor)
+        }
+    };
+    (node.key, node.value)
+}
+
+impl<K: Ord, V> TreeMap<K, V> {
+    pub fn new() -> Self {
+        TreeMap { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+            };
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        find_slot(&mut self.root, key)
+            .as_mut()
+            .map(|node| &mut node.value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Returns the previous value if the key was already present
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let slot = find_slot(&mut self.root, &key);
+        match slot {
+            Some(node) => Some(mem::replace(&mut node.value, value)),
+            None => {
+                *slot = Some(Box::new(MapNode::new(key, value)));
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = find_slot(&mut self.root, key);
+        slot.as_ref()?;
+        self.len -= 1;
+        Some(unlink(slot).1)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let slot = find_slot(&mut self.root, &key);
+        if slot.is_some() {
+            Entry::Occupied(OccupiedEntry {
+                slot,
+                len: &mut self.len,
+            })
+        } else {
+            Entry::Vacant(VacantEntry {
+                key,
+                slot,
+                len: &mut self.len,
+            })
+        }
+    }
+
+    // In-order iteration, so keys come out sorted
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for TreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = TreeMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for TreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a MapNode<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a TreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// A view into a single key of a TreeMap, mirroring HashMap::entry
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    slot: &'a mut Link<K, V>,
+    len: &'a mut usize,
+}
+
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    slot: &'a mut Link<K, V>,
+    len: &'a mut usize,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    fn node(&self) -> &MapNode<K, V> {
+        self.slot.as_ref().unwrap()
+    }
+
+    pub fn key(&self) -> &K {
+        &self.node().key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.node().value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.slot.as_mut().unwrap().value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.slot.as_mut().unwrap().value
+    }
+
+    // Replaces the value, returning the old one
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        *self.len -= 1;
+        unlink(self.slot).1
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.len += 1;
+        let node = self.slot.insert(Box::new(MapNode::new(self.key, value)));
+        &mut node.value
+    }
+}
+
+// Only keys are drawn; values are often too wide for a tree diagram
+impl<K: Display, V> RenderNode for MapNode<K, V> {
+    fn label(&self) -> String {
+        self.key.to_string()
+    }
+
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+}
+
+impl<K: Display, V> Render for TreeMap<K, V> {
+    type Node = MapNode<K, V>;
+
+    fn render_root(&self) -> Option<&Self::Node> {
+        self.root.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random keys without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn insert_get_and_remove() {
+        let mut map = TreeMap::new();
+        assert_eq!(map.insert(20, "pump"), None);
+        assert_eq!(map.insert(10, "valve"), None);
+        assert_eq!(map.insert(30, "tank"), None);
+        assert_eq!(map.insert(20, "spare pump"), Some("pump"));
+        assert_eq!(map.len(), 3);
+        assert_eq!((map.get(&20), map.get(&25)), (Some(&"spare pump"), None));
+
+        *map.get_mut(&10).unwrap() = "check valve";
+        assert_eq!(map.get(&10), Some(&"check valve"));
+        assert!(map.get_mut(&15).is_none());
+
+        // The root has two children, so its successor takes its place
+        assert_eq!(map.remove(&20), Some("spare pump"));
+        assert_eq!(map.remove(&20), None);
+        assert_eq!(map.len(), 2);
+        assert!(map.iter().eq([(&10, &"check valve"), (&30, &"tank")]));
+        assert_eq!((map.remove(&10), map.remove(&30)), (Some("check valve"), Some("tank")));
+        assert!(map.is_empty() && map.iter().next().is_none());
+    }
+
+    #[test]
+    fn entry_api() {
+        let mut map: TreeMap<&str, u32> = TreeMap::new();
+        *map.entry("pump").or_insert(0) += 1;
+        *map.entry("pump").or_insert(10) += 1;
+        assert_eq!((map.get(&"pump"), map.len()), (Some(&2), 1));
+
+        let mut calls = 0;
+        map.entry("valve").or_insert_with(|| {
+            calls += 1;
+            5
+        });
+        map.entry("valve").or_insert_with(|| {
+            calls += 1;
+            50
+        });
+        assert_eq!((map.get(&"valve"), calls, map.len()), (Some(&5), 1, 2));
+
+        // and_modify only touches occupied entries
+        map.entry("valve").and_modify(|count| *count *= 2).or_insert(0);
+        map.entry("tank").and_modify(|count| *count *= 2).or_insert(7);
+        assert_eq!((map.get(&"valve"), map.get(&"tank"), map.len()), (Some(&10), Some(&7), 3));
+        assert_eq!(*map.entry("boiler").or_default(), 0);
+        assert_eq!(map.len(), 4);
+
+        match map.entry("pump") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!((*entry.key(), *entry.get()), ("pump", 2));
+                assert_eq!(entry.insert(3), 2);
+                assert_eq!(entry.remove(), 3);
+            }
+            Entry::Vacant(_) => panic!("pump is in the map"),
+        }
+        assert_eq!((map.get(&"pump"), map.len()), (None, 3));
+        match map.entry("pump") {
+            Entry::Vacant(entry) => assert_eq!(*entry.insert(9), 9),
+            Entry::Occupied(_) => panic!("pump was removed"),
+        }
+        assert_eq!(map.len(), 4);
+        let keys: Vec<&str> = map.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, ["boiler", "pump", "tank", "valve"]);
+    }
+
+    #[test]
+    fn random_edits_match_a_btreemap() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let mut map = TreeMap::new();
+        let mut model = BTreeMap::new();
+        for step in 0..3000u64 {
+            let key = next(&mut state) % 150;
+            match next(&mut state) % 4 {
+                0 => assert_eq!(map.remove(&key), model.remove(&key)),
+                1 => {
+                    if let Entry::Occupied(entry) = map.entry(key) {
+                        assert_eq!(Some(entry.remove()), model.remove(&key));
+                    }
+                }
+                2 => {
+                    *map.entry(key).or_insert(0) += step;
+                    *model.entry(key).or_insert(0) += step;
+                }
+                _ => assert_eq!(map.insert(key, step), model.insert(key, step)),
+            }
+            assert_eq!(map.len(), model.len());
+        }
+        assert!(map.iter().eq(model.iter()));
+    }
+}
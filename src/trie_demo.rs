@@ -0,0 +1,421 @@
+// TRIE DATA STRUCTURE (Prefix Tree)
+// Each edge is one character; a key is the path from the root to a node
+// Keys sharing a prefix share the nodes for that prefix
+// Children are kept in a BTreeMap, so walks come out in lexicographic order
+// Operations: insert, remove, exact lookup, prefix iteration,
+// longest-prefix match, each in O(key length)
+// Use cases: autocomplete, tag lookup by prefix, routing tables
+
+mod radix;
+
+use std::collections::BTreeMap;
+use std::str::Chars;
+
+use crate::graph_demo::{PLANT_EQUIPMENT, SENSOR_NETWORK};
+use radix::RadixTree;
+
+#[derive(Debug)]
+struct TrieNode<V> {
+    children: BTreeMap<char, TrieNode<V>>,
+    // Some(_) when a key ends at this node
+    value: Option<V>,
+}
+
+// V defaults to (), so Trie<()> works as a plain set of strings
+#[derive(Debug)]
+pub struct Trie<V = ()> {
+    root: TrieNode<V>,
+    len: usize,
+}
+
+impl<V> TrieNode<V> {
+    fn new() -> Self {
+        TrieNode {
+            children: BTreeMap::new(),
+            value: None,
+        }
+    }
+
+    fn count_nodes(&self) -> usize {
+        1 + self.children.values().map(TrieNode::count_nodes).sum::<usize>()
+    }
+}
+
+impl<V> Trie<V> {
+    pub fn new() -> Self {
+        Trie {
+            root: TrieNode::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Nodes including the root; shows how much prefix sharing saves
+    pub fn node_count(&self) -> usize {
+        self.root.count_nodes()
+    }
+
+    // Returns the previous value if the key was already present
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for ch in key.chars() {
+            node = node.children.entry(ch).or_insert_with(TrieNode::new);
+        }
+        let previous = node.value.replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    fn find(&self, prefix: &str) -> Option<&TrieNode<V>> {
+        let mut node = &self.root;
+        for ch in prefix.chars() {
+            node = node.children.get(&ch)?;
+        }
+        Some(node)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.find(key)?.value.as_ref()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Removes the key and prunes any branch left without keys
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        // Removes the rest of the key below `node`, dropping any child
+        // that ends up with neither a value nor children
+        fn remove_from<V>(node: &mut TrieNode<V>, mut chars: Chars) -> Option<V> {
+            let Some(ch) = chars.next() else {
+                return node.value.take();
+            };
+            let child = node.children.get_mut(&ch)?;
+            let removed = remove_from(child, chars)?;
+            if child.value.is_none() && child.children.is_empty() {
+                node.children.remove(&ch);
+            }
+            Some(removed)
+        }
+
+        let removed = remove_from(&mut self.root, key.chars())?;
+        self.len -= 1;
+        Some(removed)
+    }
+
+    // Every key starting with `prefix`, in lexicographic order
+    pub fn starts_with(&self, prefix: &str) -> Iter<'_, V> {
+        Iter {
+            stack: self
+                .find(prefix)
+                .map(|node| (prefix.to_string(), node))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.starts_with("")
+    }
+
+    // Longest key that is a prefix of `text`, e.g. the most specific
+    // registered tag for "Pump-A-discharge"
+    pub fn longest_prefix_match<'t>(&self, text: &'t str) -> Option<(&'t str, &V)> {
+        let mut node = &self.root;
+        let mut best = node.value.as_ref().map(|value| (0, value));
+        for (i, ch) in text.char_indices() {
+            node = match node.children.get(&ch) {
+                Some(child) => child,
+                None => break,
+            };
+            if let Some(value) = &node.value {
+                best = Some((i + ch.len_utf8(), value));
+            }
+        }
+        best.map(|(end, value)| (&text[..end], value))
+    }
+}
+
+impl Trie<()> {
+    // Set-style insert; returns true if the key is new
+    pub fn add(&mut self, key: &str) -> bool {
+        self.insert(key, ()).is_none()
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Trie<()> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        for key in iter {
+            trie.add(key);
+        }
+        trie
+    }
+}
+
+impl<'a, V> FromIterator<(&'a str, V)> for Trie<V> {
+    fn from_iter<I: IntoIterator<Item = (&'a str, V)>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        for (key, value) in iter {
+            trie.insert(key, value);
+        }
+        trie
+    }
+}
+
+// Depth-first walk; visiting a node before its children, and children in
+// character order, yields keys in lexicographic order
+pub struct Iter<'a, V> {
+    stack: Vec<(String, &'a TrieNode<V>)>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((key, node)) = self.stack.pop() {
+            for (ch, child) in node.children.iter().rev() {
+                let mut child_key = key.clone();
+                child_key.push(*ch);
+                self.stack.push((child_key, child));
+            }
+            if let Some(value) = &node.value {
+                return Some((key, value));
+            }
+        }
+        None
+    }
+}
+
+pub fn demonstrate() {
+    println!("Trie: Prefix tree for string keys\n");
+
+    // Tag names from the graph demo, each mapped to the network it is in
+    println!("Registering equipment tags:");
+    let mut tags = Trie::new();
+    for tag in PLANT_EQUIPMENT {
+        tags.insert(tag, "process plant");
+    }
+    for tag in SENSOR_NETWORK {
+        tags.insert(tag, "sensor network");
+    }
+    for tag in ["Pump-A-01", "Pump-A-02", "Pump-B-07"] {
+        tags.insert(tag, "process plant");
+    }
+    println!("  {} tags stored in {} trie nodes", tags.len(), tags.node_count());
+    println!(
+        "  Re-registering Reactor replaces: {:?}",
+        tags.insert("Reactor", "process plant")
+    );
+
+    println!("\nExact lookups:");
+    for tag in ["Separator", "Central Hub", "Pump", "Boiler"] {
+        match tags.get(tag) {
+            Some(network) => println!("  {}: found in the {}", tag, network),
+            None => println!("  {}: Not found ✗", tag),
+        }
+    }
+
+    println!("\nPrefix searches (lexicographic order):");
+    for prefix in ["Pump-", "Pump-A", "Sensor-", "S", "Valve"] {
+        let matches: Vec<String> = tags.starts_with(prefix).map(|(tag, _)| tag).collect();
+        println!("  '{}' → {:?}", prefix, matches);
+    }
+
+    println!("\nMost specific registered tag (longest-prefix match):");
+    for text in ["Pump-A-01-discharge", "Pump-A-03", "Sensor-D/battery", "Boiler-1"] {
+        match tags.longest_prefix_match(text) {
+            Some((tag, network)) => println!("  {} → {} ({})", text, tag, network),
+            None => println!("  {} → no registered prefix", text),
+        }
+    }
+
+    println!("\nDecommissioning tags:");
+    for tag in ["Pump-B-07", "Pump-A", "Boiler"] {
+        match tags.remove(tag) {
+            Some(_) => println!("  Removed: {}", tag),
+            None => println!("  {}: Not found ✗", tag),
+        }
+    }
+    let pumps: Vec<String> = tags.starts_with("Pump").map(|(tag, _)| tag).collect();
+    println!("  Pumps left: {:?}", pumps);
+    println!("  Pump-A-01 still registered? {}", tags.contains_key("Pump-A-01"));
+    println!("  {} tags in {} nodes", tags.len(), tags.node_count());
+
+    // Set mode: no values, just the keys
+    let sensors: Trie = SENSOR_NETWORK.into_iter().collect();
+    let all: Vec<String> = sensors.iter().map(|(tag, _)| tag).collect();
+    println!("\nSensor tag set (Trie<()>): {:?}", all);
+    let mut empty = Trie::new();
+    println!("  Empty set? {}, first add new? {}", empty.is_empty(), empty.add("Hub"));
+
+    // Radix tree: chains of single-child nodes collapse into one edge
+    println!("\n--- Radix Tree (compressed trie) ---");
+    let mut radix: RadixTree<&str> = PLANT_EQUIPMENT
+        .into_iter()
+        .chain(SENSOR_NETWORK)
+        .map(|tag| (tag, "registered"))
+        .collect();
+    for tag in ["Pump-A-01", "Pump-A-02", "Pump-B-07"] {
+        radix.insert(tag, "registered");
+    }
+    let trie_nodes = PLANT_EQUIPMENT
+        .into_iter()
+        .chain(SENSOR_NETWORK)
+        .chain(["Pump-A-01", "Pump-A-02", "Pump-B-07"])
+        .collect::<Trie>()
+        .node_count();
+    println!(
+        "  {} tags: {} radix nodes vs {} trie nodes",
+        radix.len(),
+        radix.node_count(),
+        trie_nodes
+    );
+    println!("  Edge labels:");
+    for line in radix.edges() {
+        println!("    {}", line);
+    }
+
+    let pumps: Vec<String> = radix.starts_with("Pump-A").map(|(tag, _)| tag).collect();
+    println!("  'Pump-A' → {:?}", pumps);
+    let mid_edge: Vec<String> = radix.starts_with("Sen").map(|(tag, _)| tag).collect();
+    println!("  'Sen' (ends mid-edge) → {:?}", mid_edge);
+    println!(
+        "  Longest prefix of 'Pump-B-07-motor': {:?}",
+        radix.longest_prefix_match("Pump-B-07-motor").map(|(tag, _)| tag)
+    );
+    println!("  Get Control Valve: {:?}", radix.get("Control Valve"));
+
+    for tag in ["Pump-A", "Pump-A-02", "Pump-X"] {
+        match radix.remove(tag) {
+            Some(_) => println!("  Removed: {}", tag),
+            None => println!("  {}: Not found ✗", tag),
+        }
+    }
+    let remaining: Vec<String> = radix.iter().map(|(tag, _)| tag).collect();
+    println!("  Remaining: {:?}", remaining);
+    println!(
+        "  {} tags in {} nodes (merged back after removal), contains Pump-A-01? {}",
+        radix.len(),
+        radix.node_count(),
+        radix.contains_key("Pump-A-01")
+    );
+    println!("  Empty radix tree? {}", RadixTree::<()>::new().is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random operations without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // Short keys over a small alphabet collide and share prefixes often;
+    // 'é' checks that multi-byte characters are handled as one step
+    fn random_key(state: &mut u64) -> String {
+        let len = next(state) % 5;
+        (0..len).map(|_| ['a', 'b', 'é'][next(state) as usize % 3]).collect()
+    }
+
+    fn keys<V>(iter: Iter<'_, V>) -> Vec<String> {
+        iter.map(|(key, _)| key).collect()
+    }
+
+    #[test]
+    fn remove_prunes_dead_branches() {
+        let mut tags: Trie<u32> = [("Pump", 1), ("Pump-A-01", 2)].into_iter().collect();
+        assert_eq!(tags.node_count(), 10);
+        // A prefix that is not a key removes nothing
+        assert_eq!(tags.remove("Pump-A"), None);
+        assert_eq!(tags.node_count(), 10);
+        assert_eq!(tags.remove("Pump-A-01"), Some(2));
+        assert_eq!(tags.node_count(), 5);
+        // Nodes still leading to a key stay
+        tags.insert("Pump-B", 3);
+        assert_eq!(tags.remove("Pump"), Some(1));
+        assert_eq!((tags.len(), tags.node_count()), (1, 7));
+        assert_eq!(tags.remove("Pump-B"), Some(3));
+        assert_eq!((tags.len(), tags.node_count()), (0, 1));
+        assert!(tags.is_empty());
+    }
+
+    #[test]
+    fn prefix_searches_and_longest_match() {
+        let tags: Trie = ["Pump-A", "Pump-A-01", "Pump-B", "Sensor-A"].into_iter().collect();
+        assert_eq!(keys(tags.starts_with("Pump-A")), ["Pump-A", "Pump-A-01"]);
+        assert_eq!(keys(tags.starts_with("P")), ["Pump-A", "Pump-A-01", "Pump-B"]);
+        assert!(keys(tags.starts_with("Pump-C")).is_empty());
+        assert!(keys(tags.starts_with("Pump-A-01-x")).is_empty());
+
+        let best = |text| tags.longest_prefix_match(text).map(|(key, _)| key);
+        assert_eq!(best("Pump-A-01-discharge"), Some("Pump-A-01"));
+        assert_eq!(best("Pump-A-0"), Some("Pump-A"));
+        assert_eq!(best("Pump-"), None);
+        assert_eq!(best("Boiler"), None);
+    }
+
+    #[test]
+    fn empty_key_lives_at_the_root() {
+        let mut tags: Trie<u32> = Trie::new();
+        assert_eq!(tags.get(""), None);
+        assert_eq!(tags.insert("", 0), None);
+        tags.insert("Hub", 1);
+        assert_eq!((tags.len(), tags.get("")), (2, Some(&0)));
+        assert_eq!(keys(tags.iter()), ["", "Hub"]);
+        assert_eq!(tags.longest_prefix_match("Boiler"), Some(("", &0)));
+        assert_eq!(tags.longest_prefix_match("Hub-2"), Some(("Hub", &1)));
+        assert_eq!(tags.remove(""), Some(0));
+        assert_eq!(tags.remove(""), None);
+        assert_eq!((tags.len(), tags.node_count()), (1, 4));
+    }
+
+    // Random inserts and removes: contents follow a BTreeMap, and the node
+    // count is exactly one per distinct prefix of a stored key
+    #[test]
+    fn random_edits_match_a_btreemap() {
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        let mut trie = Trie::new();
+        let mut model = BTreeMap::new();
+        for step in 0..3000u32 {
+            let key = random_key(&mut state);
+            if next(&mut state).is_multiple_of(3) {
+                assert_eq!(trie.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(trie.insert(&key, step), model.insert(key.clone(), step));
+            }
+            let prefix = random_key(&mut state);
+            let expected: Vec<&String> =
+                model.keys().filter(|stored| stored.starts_with(&prefix)).collect();
+            assert_eq!(keys(trie.starts_with(&prefix)).iter().collect::<Vec<_>>(), expected);
+            let best = model.keys().rfind(|stored| key.starts_with(stored.as_str()));
+            let found = trie.longest_prefix_match(&key).map(|(found, _)| found);
+            assert_eq!(found, best.map(String::as_str));
+        }
+        let stored: Vec<(String, &u32)> = trie.iter().collect();
+        let expected: Vec<(String, &u32)> =
+            model.iter().map(|(key, step)| (key.clone(), step)).collect();
+        assert_eq!(stored, expected);
+        let prefixes: BTreeSet<&str> = model
+            .keys()
+            .flat_map(|key| key.char_indices().map(|(i, _)| &key[..i]).chain([key.as_str()]))
+            .chain([""])
+            .collect();
+        assert_eq!((trie.len(), trie.node_count()), (model.len(), prefixes.len()));
+    }
+}
@@ -0,0 +1,412 @@
+// RADIX TREE (Compressed Trie)
+// Like a trie, but a chain of nodes with one child and no key collapses
+// into a single edge labelled with the whole substring
+// Every internal node either holds a key or branches, so there are at
+// most 2n nodes for n keys no matter how long the keys are
+// Inserting splits an edge where a new key diverges; removing merges
+// an edge back once a node is left with one child and no key
+// Use cases: routers (IP prefixes), HTTP path matching, tag registries
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::mem;
+
+#[derive(Debug)]
+struct Edge<V> {
+    label: String, // Never empty
+    node: RadixNode<V>,
+}
+
+#[derive(Debug)]
+struct RadixNode<V> {
+    // Keyed by the first character of each label; sibling labels never
+    // share a first character
+    children: BTreeMap<char, Edge<V>>,
+    value: Option<V>,
+}
+
+#[derive(Debug)]
+pub struct RadixTree<V = ()> {
+    root: RadixNode<V>,
+    len: usize,
+}
+
+fn first_char(text: &str) -> Option<char> {
+    text.chars().next()
+}
+
+// Length in bytes of the longest common prefix, on a char boundary
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .take_while(|((_, x), y)| x == y)
+        .last()
+        .map_or(0, |((i, ch), _)| i + ch.len_utf8())
+}
+
+impl<V> RadixNode<V> {
+    fn new() -> Self {
+        RadixNode {
+            children: BTreeMap::new(),
+            value: None,
+        }
+    }
+
+    fn count_nodes(&self) -> usize {
+        let below: usize = self
+            .children
+            .values()
+            .map(|edge| edge.node.count_nodes())
+            .sum();
+        1 + below
+    }
+}
+
+impl<V> Edge<V> {
+    // Folds a keyless single child into this edge: "Pum" + "p-" → "Pump-"
+    fn merge_single_child(&mut self) {
+        if self.node.value.is_some() || self.node.children.len() != 1 {
+            return;
+        }
+        let (_, child) = self.node.children.pop_first().unwrap();
+        self.label.push_str(&child.label);
+        self.node = child.node;
+    }
+}
+
+impl<V> RadixTree<V> {
+    pub fn new() -> Self {
+        RadixTree {
+            root: RadixNode::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.root.count_nodes()
+    }
+
+    // Returns the previous value if the key was already present
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        let mut rest = key;
+        while let Some(ch) = first_char(rest) {
+            let edge = match node.children.entry(ch) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    // Nothing shares this character: hang the rest off a new edge
+                    let mut leaf = RadixNode::new();
+                    leaf.value = Some(value);
+                    let label = rest.to_string();
+                    entry.insert(Edge { label, node: leaf });
+                    self.len += 1;
+                    return None;
+                }
+            };
+
+            let shared = common_prefix_len(&edge.label, rest);
+            if shared < edge.label.len() {
+                // The key diverges inside the label: split the edge there
+                let tail = edge.label.split_off(shared);
+                let old_node = mem::replace(&mut edge.node, RadixNode::new());
+                let tail_first = first_char(&tail).unwrap();
+                let tail_edge = Edge {
+                    label: tail,
+                    node: old_node,
+                };
+                edge.node.children.insert(tail_first, tail_edge);
+            }
+            node = &mut edge.node;
+            rest = &rest[shared..];
+        }
+
+        let previous = node.value.replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        let mut node = &self.root;
+        let mut rest = key;
+        while let Some(ch) = first_char(rest) {
+            let edge = node.children.get(&ch)?;
+            rest = rest.strip_prefix(edge.label.as_str())?;
+            node = &edge.node;
+        }
+        node.value.as_ref()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Removes the key, then drops or merges edges so the tree stays compressed
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        fn remove_from<V>(node: &mut RadixNode<V>, rest: &str) -> Option<V> {
+            let Some(ch) = first_char(rest) else {
+                return node.value.take();
+            };
+            let edge = node.children.get_mut(&ch)?;
+            let rest = rest.strip_prefix(edge.label.as_str())?;
+            let removed = remove_from(&mut edge.node, rest)?;
+            if edge.node.value.is_none() && edge.node.children.is_empty() {
+                node.children.remove(&ch);
+            } else {
+                edge.merge_single_child();
+            }
+            Some(removed)
+        }
+
+        let removed = remove_from(&mut self.root, key)?;
+        self.len -= 1;
+        Some(removed)
+    }
+
+    // Every key starting with `prefix`, in lexicographic order; the
+    // prefix may end part-way along an edge
+    pub fn starts_with(&self, prefix: &str) -> Iter<'_, V> {
+        let mut node = &self.root;
+        let mut key = String::new();
+        let mut rest = prefix;
+        let mut stack = Vec::new();
+        loop {
+            let Some(ch) = first_char(rest) else {
+                stack.push((key, node));
+                break;
+            };
+            let Some(edge) = node.children.get(&ch) else {
+                break;
+            };
+            if let Some(after) = rest.strip_prefix(edge.label.as_str()) {
+                rest = after;
+            } else if edge.label.starts_with(rest) {
+                rest = "";
+            } else {
+                break;
+            }
+            key.push_str(&edge.label);
+            node = &edge.node;
+        }
+        Iter { stack }
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.starts_with("")
+    }
+
+    pub fn longest_prefix_match<'t>(&self, text: &'t str) -> Option<(&'t str, &V)> {
+        let mut node = &self.root;
+        let mut consumed = 0;
+        let mut best = node.value.as_ref().map(|value| (0, value));
+        while let Some(ch) = first_char(&text[consumed..]) {
+            let Some(edge) = node.children.get(&ch) else {
+                break;
+            };
+            if !text[consumed..].starts_with(edge.label.as_str()) {
+                break;
+            }
+            consumed += edge.label.len();
+            node = &edge.node;
+            if let Some(value) = &node.value {
+                best = Some((consumed, value));
+            }
+        }
+        best.map(|(end, value)| (&text[..end], value))
+    }
+
+    // One line per edge, indented by depth: shows where labels were split
+    pub fn edges(&self) -> Vec<String> {
+        fn walk<V>(node: &RadixNode<V>, depth: usize, lines: &mut Vec<String>) {
+            for edge in node.children.values() {
+                let marker = if edge.node.value.is_some() {
+                    " •"
+                } else {
+                    ""
+                };
+                lines.push(format!(
+                    "{}\"{}\"{}",
+                    "  ".repeat(depth),
+                    edge.label,
+                    marker
+                ));
+                walk(&edge.node, depth + 1, lines);
+            }
+        }
+
+        let mut lines = Vec::new();
+        walk(&self.root, 0, &mut lines);
+        lines
+    }
+}
+
+impl<'a, V> FromIterator<(&'a str, V)> for RadixTree<V> {
+    fn from_iter<I: IntoIterator<Item = (&'a str, V)>>(iter: I) -> Self {
+        let mut tree = RadixTree::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
+
+// Same lexicographic depth-first walk as the trie iterator
+pub struct Iter<'a, V> {
+    stack: Vec<(String, &'a RadixNode<V>)>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((key, node)) = self.stack.pop() {
+            for edge in node.children.values().rev() {
+                self.stack.push((key.clone() + &edge.label, &edge.node));
+            }
+            if let Some(value) = &node.value {
+                return Some((key, value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    // xorshift64: repeatable pseudo-random operations without extra crates
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // 'é' makes labels split and merge next to multi-byte characters
+    fn random_key(state: &mut u64) -> String {
+        let len = next(state) % 6;
+        (0..len).map(|_| ['a', 'b', 'é'][next(state) as usize % 3]).collect()
+    }
+
+    fn keys<V>(iter: Iter<'_, V>) -> Vec<String> {
+        iter.map(|(key, _)| key).collect()
+    }
+
+    // Below the root, every node holds a key or branches: no chain is
+    // left uncompressed, and no edge leads nowhere
+    fn compressed<V>(node: &RadixNode<V>) -> bool {
+        node.children.iter().all(|(&ch, edge)| {
+            first_char(&edge.label) == Some(ch)
+                && (edge.node.value.is_some() || edge.node.children.len() >= 2)
+                && compressed(&edge.node)
+        })
+    }
+
+    #[test]
+    fn insert_splits_edges_and_remove_merges_them() {
+        let mut tree: RadixTree<u32> = RadixTree::new();
+        tree.insert("Pump-A", 1);
+        assert_eq!(tree.edges(), ["\"Pump-A\" •"]);
+        tree.insert("Pump-B", 2);
+        assert_eq!(tree.edges(), ["\"Pump-\"", "  \"A\" •", "  \"B\" •"]);
+        // A key ending inside a label splits it with a value at the cut
+        tree.insert("Pu", 3);
+        assert_eq!(tree.edges(), ["\"Pu\" •", "  \"mp-\"", "    \"A\" •", "    \"B\" •"]);
+        assert_eq!((tree.len(), tree.node_count()), (3, 5));
+
+        assert_eq!(tree.remove("Pump-B"), Some(2));
+        assert_eq!(tree.edges(), ["\"Pu\" •", "  \"mp-A\" •"]);
+        assert_eq!(tree.remove("Pu"), Some(3));
+        assert_eq!(tree.edges(), ["\"Pump-A\" •"]);
+        // Missing keys, including ones ending mid-label, change nothing
+        assert_eq!(tree.remove("Pump"), None);
+        assert_eq!(tree.remove("Pump-A-01"), None);
+        assert_eq!((tree.len(), tree.node_count()), (1, 2));
+        assert_eq!(tree.remove("Pump-A"), Some(1));
+        assert_eq!((tree.len(), tree.node_count()), (0, 1));
+    }
+
+    #[test]
+    fn prefix_searches_may_end_mid_edge() {
+        let tree: RadixTree<()> = ["Sensor-A", "Sensor-B", "Separator"]
+            .into_iter()
+            .map(|tag| (tag, ()))
+            .collect();
+        assert_eq!(tree.edges()[0], "\"Se\"");
+        assert_eq!(keys(tree.starts_with("Sen")), ["Sensor-A", "Sensor-B"]);
+        assert_eq!(keys(tree.starts_with("Sensor-")), ["Sensor-A", "Sensor-B"]);
+        assert_eq!(keys(tree.starts_with("Sep")), ["Separator"]);
+        assert_eq!(keys(tree.starts_with("S")).len(), 3);
+        // Diverging inside a label, or running past every key, finds nothing
+        assert!(keys(tree.starts_with("Sent")).is_empty());
+        assert!(keys(tree.starts_with("Separators")).is_empty());
+    }
+
+    #[test]
+    fn longest_prefix_match_stops_at_the_last_key() {
+        let tree: RadixTree<u32> = [("Pump", 1), ("Pump-A-01", 2)].into_iter().collect();
+        let best = |text| tree.longest_prefix_match(text);
+        assert_eq!(best("Pump-A-01-motor"), Some(("Pump-A-01", &2)));
+        // Ending inside the "-A-01" label falls back to the shorter key
+        assert_eq!(best("Pump-A-0"), Some(("Pump", &1)));
+        assert_eq!(best("Pum"), None);
+        assert_eq!(best(""), None);
+    }
+
+    #[test]
+    fn empty_key_lives_at_the_root() {
+        let mut tree: RadixTree<u32> = [("Hub", 1)].into_iter().collect();
+        assert_eq!(tree.insert("", 0), None);
+        assert_eq!(tree.insert("", 5), Some(0));
+        assert_eq!((tree.len(), tree.get("")), (2, Some(&5)));
+        assert_eq!(keys(tree.iter()), ["", "Hub"]);
+        assert_eq!(tree.longest_prefix_match("Boiler"), Some(("", &5)));
+        assert_eq!(tree.remove(""), Some(5));
+        assert_eq!((tree.len(), tree.get("")), (1, None));
+        assert_eq!(tree.longest_prefix_match("Boiler"), None);
+    }
+
+    // Random inserts and removes: contents follow a BTreeMap and the tree
+    // stays fully compressed after every edit
+    #[test]
+    fn random_edits_match_a_btreemap() {
+        let mut state = 0xD1B5_4A32_D192_ED03;
+        let mut tree = RadixTree::new();
+        let mut model = BTreeMap::new();
+        for step in 0..3000u32 {
+            let key = random_key(&mut state);
+            if next(&mut state).is_multiple_of(3) {
+                assert_eq!(tree.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(tree.insert(&key, step), model.insert(key.clone(), step));
+            }
+            assert!(compressed(&tree.root));
+            assert!(tree.node_count() <= 2 * tree.len() + 1);
+
+            let probe = random_key(&mut state);
+            assert_eq!(tree.get(&probe), model.get(&probe));
+            let expected: Vec<&String> =
+                model.keys().filter(|stored| stored.starts_with(&probe)).collect();
+            assert_eq!(keys(tree.starts_with(&probe)).iter().collect::<Vec<_>>(), expected);
+            let best = model.keys().rfind(|stored| probe.starts_with(stored.as_str()));
+            let found = tree.longest_prefix_match(&probe).map(|(found, _)| found);
+            assert_eq!(found, best.map(String::as_str));
+        }
+        let stored: Vec<(String, &u32)> = tree.iter().collect();
+        let expected: Vec<(String, &u32)> =
+            model.iter().map(|(key, step)| (key.clone(), step)).collect();
+        assert_eq!((stored, tree.len()), (expected, model.len()));
+    }
+}